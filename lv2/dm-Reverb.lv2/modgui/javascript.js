@@ -3,7 +3,7 @@ function(event) {
     switch (symbol) {
         case "reverse":
             const reverse = event.icon.find("[mod-port-symbol=reverse]");
-            if(value == 1) {
+            if(value > 0) {
               reverse.addClass("on");
             } else {
               reverse.removeClass("on");
@@ -1,7 +1,8 @@
 extern crate lv2;
 extern crate reverb;
 use lv2::prelude::*;
-use reverb::{Params, Reverb};
+use reverb::{Params, Reverb, RoomGeometry, RoomReflections, Settings};
+use std::any::Any;
 
 #[derive(PortCollection)]
 struct Ports {
@@ -19,21 +20,97 @@ struct Ports {
   input_right: InputPort<InPlaceAudio>,
   output_left: OutputPort<InPlaceAudio>,
   output_right: OutputPort<InPlaceAudio>,
+  quality: InputPort<InPlaceControl>,
+  early: InputPort<InPlaceControl>,
+  early_level: InputPort<InPlaceControl>,
+  room_width: InputPort<InPlaceControl>,
+  room_depth: InputPort<InPlaceControl>,
+  room_height: InputPort<InPlaceControl>,
+  source_x: InputPort<InPlaceControl>,
+  source_y: InputPort<InPlaceControl>,
+  source_z: InputPort<InPlaceControl>,
+  listener_x: InputPort<InPlaceControl>,
+  listener_y: InputPort<InPlaceControl>,
+  listener_z: InputPort<InPlaceControl>,
+  wall_absorption: InputPort<InPlaceControl>,
+  low_decay: InputPort<InPlaceControl>,
+  mid_decay: InputPort<InPlaceControl>,
+  high_decay: InputPort<InPlaceControl>,
+  low_crossover: InputPort<InPlaceControl>,
+  high_crossover: InputPort<InPlaceControl>,
+  low_cut: InputPort<InPlaceControl>,
+  low_cut_resonance: InputPort<InPlaceControl>,
+  low_cut_slope: InputPort<InPlaceControl>,
+  high_cut: InputPort<InPlaceControl>,
+  high_cut_resonance: InputPort<InPlaceControl>,
+  high_cut_slope: InputPort<InPlaceControl>,
+  eq: InputPort<InPlaceControl>,
+  low_shelf_freq: InputPort<InPlaceControl>,
+  low_shelf_gain: InputPort<InPlaceControl>,
+  peak_freq: InputPort<InPlaceControl>,
+  peak_gain: InputPort<InPlaceControl>,
+  peak_q: InputPort<InPlaceControl>,
+  high_shelf_freq: InputPort<InPlaceControl>,
+  high_shelf_gain: InputPort<InPlaceControl>,
+  shimmer_pitch: InputPort<InPlaceControl>,
+  shimmer_quantize: InputPort<InPlaceControl>,
+  shimmer_2_pitch: InputPort<InPlaceControl>,
+  shimmer_2_level: InputPort<InPlaceControl>,
+  shimmer_spread: InputPort<InPlaceControl>,
+  shimmer_mode: InputPort<InPlaceControl>,
+  reverse_tail: InputPort<InPlaceControl>,
+  reverse_tail_length: InputPort<InPlaceControl>,
+  reverse_tail_sync: InputPort<InPlaceControl>,
+  reverse_tail_division: InputPort<InPlaceControl>,
+  tempo: InputPort<InPlaceControl>,
+  reverse_crossfade: InputPort<InPlaceControl>,
+  width: InputPort<InPlaceControl>,
+  mono_bass: InputPort<InPlaceControl>,
+  mono_bass_freq: InputPort<InPlaceControl>,
+  injection_distribution: InputPort<InPlaceControl>,
+  injection_rotation: InputPort<InPlaceControl>,
+  injection_spread: InputPort<InPlaceControl>,
+  lfo_shape: InputPort<InPlaceControl>,
+  lfo_phase_spread: InputPort<InPlaceControl>,
+  lfo_rate_spread: InputPort<InPlaceControl>,
+  lfo_seed: InputPort<InPlaceControl>,
+  grain_size: InputPort<InPlaceControl>,
+  grain_density: InputPort<InPlaceControl>,
+  grain_window: InputPort<InPlaceControl>,
+  grain_curve: InputPort<InPlaceControl>,
+  grain_pitch_jitter: InputPort<InPlaceControl>,
+  grain_reverse: InputPort<InPlaceControl>,
+  mix_mode: InputPort<InPlaceControl>,
+  dry_level: InputPort<InPlaceControl>,
+  wet_level: InputPort<InPlaceControl>,
+  kill_dry: InputPort<InPlaceControl>,
+  early_left: Option<OutputPort<InPlaceAudio>>,
+  early_right: Option<OutputPort<InPlaceAudio>>,
+  late_left: Option<OutputPort<InPlaceAudio>>,
+  late_right: Option<OutputPort<InPlaceAudio>>,
+  dry_left: Option<OutputPort<InPlaceAudio>>,
+  dry_right: Option<OutputPort<InPlaceAudio>>,
+}
+
+#[derive(FeatureCollection)]
+struct Features<'a> {
+  schedule: Schedule<'a, DmReverb>,
 }
 
 #[uri("https://github.com/davemollen/dm-Reverb")]
 struct DmReverb {
   reverb: Reverb,
   params: Params,
+  room_geometry: Option<RoomGeometry>,
 }
 
 impl Plugin for DmReverb {
   // Tell the framework which ports this plugin has.
   type Ports = Ports;
 
-  // We don't need any special host features; We can leave them out.
+  // The room reflections are computed on the worker thread of the host.
   type InitFeatures = ();
-  type AudioFeatures = ();
+  type AudioFeatures = Features<'static>;
 
   // Create a new instance of the plugin; Trivial in this case.
   fn new(_plugin_info: &PluginInfo, _features: &mut ()) -> Option<Self> {
@@ -42,37 +119,176 @@ impl Plugin for DmReverb {
     Some(Self {
       reverb: Reverb::new(sample_rate),
       params: Params::new(sample_rate),
+      room_geometry: None,
     })
   }
 
+  // Clears the reverb when the host activates the plugin, so renders start from the same state.
+  fn activate(&mut self, _features: &mut ()) {
+    self.reverb.reset();
+  }
+
   // Process a chunk of audio. The audio ports are dereferenced to slices, which the plugin
   // iterates over.
-  fn run(&mut self, ports: &mut Ports, _features: &mut (), _sample_count: u32) {
-    self.params.set(
-      ports.reverse.get(),
-      ports.predelay.get(),
-      ports.size.get(),
-      ports.speed.get(),
-      ports.depth.get() * 0.01,
-      ports.absorb.get() * 0.01,
-      ports.decay.get() * 0.01,
-      ports.tilt.get() * 0.01,
-      ports.shimmer.get() * 0.01,
-      ports.mix.get() * 0.01,
-    );
+  fn run(&mut self, ports: &mut Ports, features: &mut Features<'static>, _sample_count: u32) {
+    let room_geometry = RoomGeometry {
+      dimensions: (
+        ports.room_width.get(),
+        ports.room_depth.get(),
+        ports.room_height.get(),
+      ),
+      source: (
+        ports.source_x.get() * 0.01,
+        ports.source_y.get() * 0.01,
+        ports.source_z.get() * 0.01,
+      ),
+      listener: (
+        ports.listener_x.get() * 0.01,
+        ports.listener_y.get() * 0.01,
+        ports.listener_z.get() * 0.01,
+      ),
+      absorption: ports.wall_absorption.get() * 0.01,
+    };
+    // The reflections are applied in work_response. When the work queue is full, the next run tries again.
+    if self.room_geometry != Some(room_geometry)
+      && features.schedule.schedule_work(room_geometry).is_ok()
+    {
+      self.room_geometry = Some(room_geometry);
+    }
+
+    self.params.set(&Settings {
+      reverse: ports.reverse.get(),
+      predelay: ports.predelay.get(),
+      size: ports.size.get(),
+      speed: ports.speed.get(),
+      depth: ports.depth.get() * 0.01,
+      absorb: ports.absorb.get() * 0.01,
+      decay: ports.decay.get() * 0.01,
+      tilt: ports.tilt.get() * 0.01,
+      shimmer: ports.shimmer.get() * 0.01,
+      mix: ports.mix.get() * 0.01,
+      quality: ports.quality.get() as i32,
+      early: ports.early.get() as i32,
+      early_level: ports.early_level.get() * 0.01,
+      low_decay: ports.low_decay.get(),
+      mid_decay: ports.mid_decay.get(),
+      high_decay: ports.high_decay.get(),
+      low_crossover: ports.low_crossover.get(),
+      high_crossover: ports.high_crossover.get(),
+      low_cut: ports.low_cut.get(),
+      low_cut_resonance: ports.low_cut_resonance.get(),
+      low_cut_slope: ports.low_cut_slope.get() as i32,
+      high_cut: ports.high_cut.get(),
+      high_cut_resonance: ports.high_cut_resonance.get(),
+      high_cut_slope: ports.high_cut_slope.get() as i32,
+      eq: ports.eq.get() == 1.,
+      low_shelf_freq: ports.low_shelf_freq.get(),
+      low_shelf_gain: ports.low_shelf_gain.get(),
+      peak_freq: ports.peak_freq.get(),
+      peak_gain: ports.peak_gain.get(),
+      peak_q: ports.peak_q.get(),
+      high_shelf_freq: ports.high_shelf_freq.get(),
+      high_shelf_gain: ports.high_shelf_gain.get(),
+      shimmer_pitch: ports.shimmer_pitch.get(),
+      shimmer_quantize: ports.shimmer_quantize.get() == 1.,
+      shimmer_2_pitch: ports.shimmer_2_pitch.get(),
+      shimmer_2_level: ports.shimmer_2_level.get() * 0.01,
+      shimmer_spread: ports.shimmer_spread.get() * 0.01,
+      shimmer_mode: ports.shimmer_mode.get() as i32,
+      reverse_tail: ports.reverse_tail.get() == 1.,
+      reverse_tail_length: ports.reverse_tail_length.get(),
+      reverse_tail_sync: ports.reverse_tail_sync.get() == 1.,
+      reverse_tail_division: ports.reverse_tail_division.get() as i32,
+      tempo: ports.tempo.get(),
+      reverse_crossfade: ports.reverse_crossfade.get(),
+      width: ports.width.get() * 0.01,
+      mono_bass: ports.mono_bass.get() == 1.,
+      mono_bass_freq: ports.mono_bass_freq.get(),
+      injection_distribution: ports.injection_distribution.get() * 0.01,
+      injection_rotation: ports.injection_rotation.get() * 0.01,
+      injection_spread: ports.injection_spread.get() * 0.01,
+      lfo_shape: ports.lfo_shape.get() as i32,
+      lfo_phase_spread: ports.lfo_phase_spread.get() * 0.01,
+      lfo_rate_spread: ports.lfo_rate_spread.get() * 0.01,
+      lfo_seed: ports.lfo_seed.get() as i32,
+      grain_size: ports.grain_size.get(),
+      grain_density: ports.grain_density.get() as i32,
+      grain_window: ports.grain_window.get() as i32,
+      grain_curve: ports.grain_curve.get() * 0.01,
+      grain_pitch_jitter: ports.grain_pitch_jitter.get(),
+      grain_reverse: ports.grain_reverse.get() * 0.01,
+      mix_mode: ports.mix_mode.get() as i32,
+      dry_level: ports.dry_level.get(),
+      wet_level: ports.wet_level.get(),
+      kill_dry: ports.kill_dry.get() == 1.,
+    });
 
     let input_channels = ports.input_left.iter().zip(ports.input_right.iter());
     let output_channels = ports.output_left.iter().zip(ports.output_right.iter());
+    let aux_outputs = [
+      (ports.early_left.as_deref(), ports.early_right.as_deref()),
+      (ports.late_left.as_deref(), ports.late_right.as_deref()),
+      (ports.dry_left.as_deref(), ports.dry_right.as_deref()),
+    ];
+
+    // The early, late and dry buses are only computed when the host connects one of their ports
+    if aux_outputs
+      .iter()
+      .all(|(left, right)| left.is_none() && right.is_none())
+    {
+      input_channels.zip(output_channels).for_each(
+        |((input_left, input_right), (output_left, output_right))| {
+          let input = (input_left.get(), input_right.get());
+          let output = self.reverb.process(input, &mut self.params);
+          output_left.set(output.0);
+          output_right.set(output.1);
+        },
+      );
+    } else {
+      input_channels.zip(output_channels).enumerate().for_each(
+        |(i, ((input_left, input_right), (output_left, output_right)))| {
+          let input = (input_left.get(), input_right.get());
+          let (output, early, late) = self.reverb.process_with_buses(input, &mut self.params);
+          output_left.set(output.0);
+          output_right.set(output.1);
+          for ((left, right), bus) in aux_outputs.iter().zip([early, late, input]) {
+            if let Some(left) = left {
+              left[i].set(bus.0);
+            }
+            if let Some(right) = right {
+              right[i].set(bus.1);
+            }
+          }
+        },
+      );
+    }
+  }
+
+  fn extension_data(uri: &Uri) -> Option<&'static dyn Any> {
+    match_extensions![uri, WorkerDescriptor<Self>]
+  }
+}
+
+impl Worker for DmReverb {
+  type WorkData = RoomGeometry;
+  type ResponseData = RoomReflections;
+
+  fn work(
+    response_handler: &ResponseHandler<Self>,
+    room_geometry: Self::WorkData,
+  ) -> Result<(), WorkerError> {
+    response_handler
+      .respond(room_geometry.compute_reflections())
+      .map_err(|_| WorkerError::Unknown)
+  }
 
-    input_channels.zip(output_channels).for_each(
-      |((input_left, input_right), (output_left, output_right))| {
-        let output = self
-          .reverb
-          .process((input_left.get(), input_right.get()), &mut self.params);
-        output_left.set(output.0);
-        output_right.set(output.1);
-      },
-    );
+  fn work_response(
+    &mut self,
+    room_reflections: Self::ResponseData,
+    _features: &mut Self::AudioFeatures,
+  ) -> Result<(), WorkerError> {
+    self.reverb.set_room_reflections(room_reflections);
+    Ok(())
   }
 }
 
@@ -0,0 +1,93 @@
+use crate::reverb_parameters::ReverbParameters;
+use nih_plug::prelude::{AtomicF32, Params};
+use reverb::{ImpulseResponse, Params as ProcessParams, SPECTROGRAM_BANDS};
+use std::sync::{
+  atomic::{AtomicBool, AtomicU32, Ordering},
+  Mutex,
+};
+
+// The preview doesn't depend on the sample rate of the host
+const SAMPLE_RATE: f32 = 44100.;
+const LENGTH: f32 = 8.;
+const DECAY_POINTS: usize = 128;
+const SPECTROGRAM_FRAMES: usize = 64;
+
+#[derive(Default)]
+pub struct DecayPreviewData {
+  pub energy_decay: Vec<f32>,
+  pub spectrogram: Vec<[f32; SPECTROGRAM_BANDS.len()]>,
+}
+
+/// The latest impulse response analysis, published by the background task for the editor. `version` changes
+/// whenever the editor should redraw the preview.
+pub struct DecayPreview {
+  pub version: AtomicU32,
+  pub rt60: AtomicF32,
+  pub show_spectrogram: AtomicBool,
+  pub data: Mutex<DecayPreviewData>,
+}
+
+impl Default for DecayPreview {
+  fn default() -> Self {
+    Self {
+      version: AtomicU32::new(0),
+      // Stays NaN until the first render and is infinite when the tail outlasts the render
+      rt60: AtomicF32::new(f32::NAN),
+      show_spectrogram: AtomicBool::new(false),
+      data: Mutex::new(DecayPreviewData::default()),
+    }
+  }
+}
+
+impl DecayPreview {
+  pub fn toggle_spectrogram(&self) {
+    self.show_spectrogram.fetch_xor(true, Ordering::Relaxed);
+    self.version.fetch_add(1, Ordering::Relaxed);
+  }
+}
+
+/// Runs on the background thread every time the audio thread polls it. The preview is only rendered once all
+/// param values are the same as at the previous poll, so a moving knob doesn't queue up renders.
+#[derive(Default)]
+pub struct DecayPreviewRenderer {
+  polled_values: Vec<f32>,
+  rendered_values: Vec<f32>,
+}
+
+impl DecayPreviewRenderer {
+  pub fn update(&mut self, params: &ReverbParameters, tempo: f32, decay_preview: &DecayPreview) {
+    let mut values: Vec<f32> = params
+      .param_map()
+      .iter()
+      // The pointers stay valid for as long as `params` is borrowed
+      .map(|(_, param_ptr, _)| unsafe { param_ptr.modulated_normalized_value() })
+      .collect();
+    values.push(tempo);
+
+    let is_settled = values == self.polled_values;
+    self.polled_values = values;
+    if !is_settled || self.polled_values == self.rendered_values {
+      return;
+    }
+    self.rendered_values = self.polled_values.clone();
+
+    let mut process_params = ProcessParams::new(SAMPLE_RATE);
+    params.set_process_params(&mut process_params, tempo);
+    let impulse_response = ImpulseResponse::render(
+      SAMPLE_RATE,
+      LENGTH,
+      params.get_room_geometry().compute_reflections(),
+      &mut process_params,
+    );
+
+    *decay_preview.data.lock().unwrap() = DecayPreviewData {
+      energy_decay: impulse_response.get_energy_decay(DECAY_POINTS),
+      spectrogram: impulse_response.get_spectrogram(SPECTROGRAM_FRAMES),
+    };
+    decay_preview.rt60.store(
+      impulse_response.get_rt60().unwrap_or(f32::INFINITY),
+      Ordering::Relaxed,
+    );
+    decay_preview.version.fetch_add(1, Ordering::Relaxed);
+  }
+}
@@ -1,33 +1,53 @@
-#[path = "./editor/components/param_checkbox.rs"]
-mod param_checkbox;
 use nih_plug_vizia::{vizia::style::FontWeightKeyword, vizia_assets};
-use param_checkbox::ParamCheckbox;
 #[path = "./editor/components/param_knob.rs"]
 mod param_knob;
 use param_knob::{ParamKnob, ParamKnobSize};
+#[path = "./editor/components/level_meter.rs"]
+mod level_meter;
+use level_meter::{LevelMeter, TankMeter};
+#[path = "./editor/components/response_curve.rs"]
+mod response_curve;
+use response_curve::ResponseCurve;
+#[path = "./editor/components/decay_preview.rs"]
+mod decay_preview;
+use decay_preview::DecayPreviewView;
+#[path = "./editor/components/preset_bar.rs"]
+mod preset_bar;
+use preset_bar::PresetBar;
+#[path = "./editor/components/scale_selector.rs"]
+mod scale_selector;
+use scale_selector::ScaleSelector;
+#[path = "./editor/components/size_handle.rs"]
+mod size_handle;
+use size_handle::SizeHandle;
 mod ui_data;
-use crate::reverb_parameters::ReverbParameters;
+use crate::{decay_preview::DecayPreview, meters::Meters, reverb_parameters::ReverbParameters};
 use nih_plug::params::Param;
 use nih_plug::prelude::Editor;
 use nih_plug_vizia::vizia::{
   model::Model,
   modifiers::{LayoutModifiers, StyleModifiers, TextModifiers},
-  prelude::Units::{Pixels, Stretch},
+  prelude::Units::{Auto, Percentage, Pixels, Stretch},
   views::{HStack, Label, VStack},
 };
 use nih_plug_vizia::{assets, create_vizia_editor, ViziaState, ViziaTheming};
-use std::sync::Arc;
-pub use ui_data::{ParamChangeEvent, UiData};
+use std::sync::{Arc, RwLock};
+pub use ui_data::{ParamChangeEvent, PresetEvent, UiData};
 
 const STYLE: &str = include_str!("./editor/style.css");
 
+// The initial and smallest size of the editor, before scaling
+pub(crate) const DEFAULT_SIZE: (u32, u32) = (480, 456);
+
 // Makes sense to also define this here, makes it a bit easier to keep track of
-pub(crate) fn default_state() -> Arc<ViziaState> {
-  ViziaState::new(|| (480, 296))
+pub(crate) fn default_state(editor_size: Arc<RwLock<(u32, u32)>>) -> Arc<ViziaState> {
+  ViziaState::new(move || *editor_size.read().unwrap())
 }
 
 pub(crate) fn create(
   params: Arc<ReverbParameters>,
+  meters: Arc<Meters>,
+  decay_preview: Arc<DecayPreview>,
   editor_state: Arc<ViziaState>,
 ) -> Option<Box<dyn Editor>> {
   create_vizia_editor(
@@ -39,13 +59,17 @@ pub(crate) fn create(
       cx.set_default_font(&[vizia_assets::ROBOTO]);
       cx.add_stylesheet(STYLE).ok();
 
-      UiData {
-        params: params.clone(),
-        gui_context: gui_context.clone(),
-      }
+      UiData::new(
+        params.clone(),
+        meters.clone(),
+        decay_preview.clone(),
+        gui_context.clone(),
+      )
       .build(cx);
 
       VStack::new(cx, |cx| {
+        PresetBar::new(cx).bottom(Pixels(12.0));
+
         HStack::new(cx, |cx| {
           ParamKnob::new(
             cx,
@@ -69,18 +93,16 @@ pub(crate) fn create(
               |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
               ParamKnobSize::Regular,
             );
-            ParamCheckbox::new(
+            ParamKnob::new(
               cx,
               params.reverse.name(),
               UiData::params,
               params.reverse.as_ptr(),
               |params| &params.reverse,
               |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
+              ParamKnobSize::Regular,
             )
-            .top(Pixels(4.))
-            .left(Stretch(1.0))
-            .right(Stretch(1.0))
-            .height(Pixels(92.));
+            .top(Pixels(4.));
           })
           .child_space(Stretch(1.0))
           .left(Pixels(16.0));
@@ -163,23 +185,50 @@ pub(crate) fn create(
             |param_ptr, val| ParamChangeEvent::SetParam(param_ptr, val),
             ParamKnobSize::Regular,
           );
-        });
-
-        Label::new(cx, "dm-Reverb")
-          .font_size(22.0)
-          .font_weight(FontWeightKeyword::Bold)
-          .border_radius(Pixels(16.0))
-          .border_width(Pixels(1.))
-          .border_color("#2c5494")
-          .background_color("#3c6ab5")
-          .child_space(Stretch(1.0))
-          .child_top(Pixels(1.0))
-          .child_bottom(Pixels(5.0))
-          .width(Pixels(144.0))
-          .left(Stretch(1.0));
+        })
+        .height(Stretch(3.0));
+
+        HStack::new(cx, |cx| {
+          LevelMeter::new(cx, "In", UiData::meters, |meters| &meters.input);
+          LevelMeter::new(cx, "Wet", UiData::meters, |meters| &meters.wet);
+          LevelMeter::new(cx, "Out", UiData::meters, |meters| &meters.output);
+          TankMeter::new(cx, UiData::meters);
+        })
+        .height(Auto)
+        .col_between(Pixels(12.0))
+        .top(Pixels(16.0));
+
+        HStack::new(cx, |cx| {
+          ResponseCurve::new(cx, UiData::params);
+          DecayPreviewView::new(cx, UiData::decay_preview);
+        })
+        .height(Stretch(1.0))
+        .col_between(Pixels(16.0))
+        .top(Pixels(16.0));
+
+        HStack::new(cx, |cx| {
+          ScaleSelector::new(cx).top(Stretch(1.0));
+
+          Label::new(cx, "dm-Reverb")
+            .font_size(22.0)
+            .font_weight(FontWeightKeyword::Bold)
+            .border_radius(Pixels(16.0))
+            .border_width(Pixels(1.))
+            .border_color("#2c5494")
+            .background_color("#3c6ab5")
+            .child_space(Stretch(1.0))
+            .child_top(Pixels(1.0))
+            .child_bottom(Pixels(5.0))
+            .width(Percentage(32.0));
+        })
+        .height(Auto)
+        .col_between(Stretch(1.0))
+        .top(Pixels(12.0));
       })
       .child_space(Pixels(16.0))
       .background_color("#505050");
+
+      SizeHandle::new(cx, params.editor_size.clone());
     },
   )
 }
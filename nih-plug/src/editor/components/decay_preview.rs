@@ -0,0 +1,131 @@
+use crate::decay_preview::DecayPreview;
+use nih_plug_vizia::vizia::{
+  binding::{Binding, Lens},
+  layout::Units::Auto,
+  modifiers::TextModifiers,
+  prelude::{
+    ActionModifiers, Canvas, Context, DrawContext, LayoutModifiers, LensExt,
+    Units::{Pixels, Stretch},
+  },
+  vg,
+  view::{Handle, View},
+  views::{Label, ZStack},
+};
+use reverb::SPECTROGRAM_BANDS;
+use std::sync::{atomic::Ordering, Arc};
+
+// Levels from 0 down to this many decibels are drawn
+const DB_RANGE: f32 = 90.;
+
+pub struct DecayPreviewView {
+  energy_decay: Vec<f32>,
+  spectrogram: Option<Vec<[f32; SPECTROGRAM_BANDS.len()]>>,
+}
+
+impl DecayPreviewView {
+  /// Draws the energy decay of the rendered impulse response with its RT60. Clicking the preview shows the
+  /// octave band spectrogram underneath the decay.
+  pub fn new<L>(cx: &mut Context, lens: L) -> Handle<ZStack>
+  where
+    L: 'static + Lens<Target = Arc<DecayPreview>> + Copy + Send + Sync,
+    <L as Lens>::Source: 'static,
+  {
+    ZStack::new(cx, |cx| {
+      Binding::new(
+        cx,
+        lens.map(|decay_preview| decay_preview.version.load(Ordering::Relaxed)),
+        move |cx, _| {
+          let decay_preview = lens.get(cx);
+          let data = decay_preview.data.lock().unwrap();
+          Self {
+            energy_decay: data.energy_decay.clone(),
+            spectrogram: if decay_preview.show_spectrogram.load(Ordering::Relaxed) {
+              Some(data.spectrogram.clone())
+            } else {
+              None
+            },
+          }
+          .build(cx, |_| {})
+          .width(Stretch(1.0))
+          .height(Stretch(1.0));
+        },
+      );
+
+      Label::new(
+        cx,
+        lens.map(|decay_preview| {
+          let rt60 = decay_preview.rt60.load(Ordering::Relaxed);
+          if rt60.is_nan() {
+            String::from("RT60 ...")
+          } else if rt60.is_infinite() {
+            String::from("RT60 > 8 s")
+          } else {
+            format!("RT60 {:.2} s", rt60)
+          }
+        }),
+      )
+      .font_size(11.0)
+      .size(Auto)
+      .left(Stretch(1.0))
+      .right(Pixels(4.0))
+      .top(Pixels(2.0));
+    })
+    .on_mouse_down(move |cx, _| lens.get(cx).toggle_spectrogram())
+  }
+}
+
+impl View for DecayPreviewView {
+  fn element(&self) -> Option<&'static str> {
+    Some("decay-preview")
+  }
+
+  fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+    let bounds = cx.bounds();
+
+    let mut background = vg::Path::new();
+    background.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+    canvas.fill_path(
+      &mut background,
+      &vg::Paint::color(vg::Color::rgb(54, 54, 54)),
+    );
+
+    if let Some(spectrogram) = &self.spectrogram {
+      let frame_width = bounds.w / spectrogram.len() as f32;
+      let band_height = bounds.h / SPECTROGRAM_BANDS.len() as f32;
+      for (frame, levels) in spectrogram.iter().enumerate() {
+        for (band, level) in levels.iter().enumerate() {
+          let alpha = ((level + DB_RANGE) / DB_RANGE).clamp(0., 1.);
+          let mut cell = vg::Path::new();
+          cell.rect(
+            bounds.x + frame as f32 * frame_width,
+            bounds.y + bounds.h - (band + 1) as f32 * band_height,
+            frame_width,
+            band_height,
+          );
+          canvas.fill_path(
+            &mut cell,
+            &vg::Paint::color(vg::Color::rgba(60, 106, 181, (alpha * 255.) as u8)),
+          );
+        }
+      }
+    }
+
+    if self.energy_decay.is_empty() {
+      return;
+    }
+    let mut curve = vg::Path::new();
+    for (i, level) in self.energy_decay.iter().enumerate() {
+      let x = bounds.x + bounds.w * i as f32 / self.energy_decay.len() as f32;
+      let y = bounds.y + level.clamp(-DB_RANGE, 0.) / -DB_RANGE * bounds.h;
+      if i == 0 {
+        curve.move_to(x, y);
+      } else {
+        curve.line_to(x, y);
+      }
+    }
+    canvas.stroke_path(
+      &mut curve,
+      &vg::Paint::color(vg::Color::rgb(255, 180, 51)).with_line_width(1.5),
+    );
+  }
+}
@@ -0,0 +1,118 @@
+use crate::meters::{Level, Meters};
+use nih_plug::util::gain_to_db;
+use nih_plug_vizia::{
+  vizia::{
+    binding::Lens,
+    layout::Units::Auto,
+    modifiers::TextModifiers,
+    prelude::{
+      Context, LayoutModifiers, LensExt,
+      Units::{Pixels, Stretch},
+    },
+    style::FontWeightKeyword,
+    view::Handle,
+    views::{Label, VStack},
+  },
+  widgets::PeakMeter,
+};
+use std::{
+  sync::{atomic::Ordering, Arc},
+  time::Duration,
+};
+
+const PEAK_HOLD_TIME: Duration = Duration::from_millis(600);
+
+pub struct LevelMeter {}
+
+impl LevelMeter {
+  /// Draws a peak meter per channel, with the loudest RMS level of both channels underneath.
+  pub fn new<'a, L, F>(
+    cx: &'a mut Context,
+    name: &'a str,
+    lens: L,
+    meters_to_levels: F,
+  ) -> Handle<'a, VStack>
+  where
+    L: 'static + Lens<Target = Arc<Meters>> + Copy + Send + Sync,
+    <L as Lens>::Source: 'static,
+    F: 'static + Fn(&Meters) -> &[Level; 2] + Copy + Send + Sync,
+  {
+    VStack::new(cx, |cx| {
+      Label::new(cx, name)
+        .font_size(12.0)
+        .font_weight(FontWeightKeyword::SemiBold);
+
+      for channel in 0..2 {
+        PeakMeter::new(
+          cx,
+          lens.map(move |meters| {
+            gain_to_db(
+              meters_to_levels(meters)[channel]
+                .peak
+                .load(Ordering::Relaxed),
+            )
+          }),
+          Some(PEAK_HOLD_TIME),
+        )
+        .width(Stretch(1.0))
+        .height(Pixels(6.0));
+      }
+
+      Label::new(
+        cx,
+        lens.map(move |meters| {
+          let levels = meters_to_levels(meters);
+          let rms = levels[0]
+            .rms
+            .load(Ordering::Relaxed)
+            .max(levels[1].rms.load(Ordering::Relaxed));
+          format!("RMS {:.1} dB", gain_to_db(rms))
+        }),
+      )
+      .font_size(11.0);
+    })
+    .width(Stretch(1.0))
+    .height(Auto)
+    .row_between(Pixels(2.0))
+  }
+}
+
+pub struct TankMeter {}
+
+impl TankMeter {
+  /// Shows the level inside the tank and how much gain compensation holds it back, so saturation and
+  /// self-oscillation are visible.
+  pub fn new<L>(cx: &mut Context, lens: L) -> Handle<VStack>
+  where
+    L: 'static + Lens<Target = Arc<Meters>> + Copy + Send + Sync,
+    <L as Lens>::Source: 'static,
+  {
+    VStack::new(cx, |cx| {
+      Label::new(cx, "Tank")
+        .font_size(12.0)
+        .font_weight(FontWeightKeyword::SemiBold);
+
+      PeakMeter::new(
+        cx,
+        lens.map(|meters| gain_to_db(meters.tank_level.load(Ordering::Relaxed))),
+        Some(PEAK_HOLD_TIME),
+      )
+      .width(Stretch(1.0))
+      .height(Pixels(6.0));
+
+      Label::new(
+        cx,
+        lens.map(|meters| {
+          format!(
+            "Comp {:.1} dB",
+            gain_to_db(meters.gain_compensation.load(Ordering::Relaxed))
+          )
+        }),
+      )
+      .font_size(11.0);
+    })
+    .width(Stretch(1.0))
+    .height(Auto)
+    .row_between(Pixels(2.0))
+  }
+}
@@ -0,0 +1,88 @@
+use crate::editor::{PresetEvent, UiData};
+use nih_plug_vizia::vizia::{
+  binding::Binding,
+  layout::Units::Auto,
+  modifiers::TextModifiers,
+  prelude::{
+    ActionModifiers, Context, EmitContext, LayoutModifiers, LensExt, StyleModifiers,
+    Units::{Pixels, Stretch},
+  },
+  view::Handle,
+  views::{Dropdown, HStack, Label, PopupEvent, TextEvent, Textbox, VStack},
+};
+
+fn preset_button<'a>(cx: &'a mut Context, text: &str, event: PresetEvent) -> Handle<'a, Label> {
+  Label::new(cx, text)
+    .class("preset-button")
+    .on_mouse_down(move |cx, _| cx.emit(event.clone()))
+}
+
+pub struct PresetBar {}
+
+impl PresetBar {
+  /// Browses the factory and user presets, manages the user presets and switches between two A/B slots.
+  pub fn new(cx: &mut Context) -> Handle<HStack> {
+    HStack::new(cx, |cx| {
+      preset_button(cx, "<", PresetEvent::Previous);
+      Dropdown::new(
+        cx,
+        |cx| {
+          Label::new(
+            cx,
+            UiData::preset_name.map(|name| {
+              if name.is_empty() {
+                String::from("Presets")
+              } else {
+                name.clone()
+              }
+            }),
+          )
+          .class("preset-name")
+        },
+        |cx| {
+          Binding::new(cx, UiData::preset_names, |cx, preset_names| {
+            VStack::new(cx, |cx| {
+              for (index, name) in preset_names.get(cx).into_iter().enumerate() {
+                Label::new(cx, &name)
+                  .class("preset-item")
+                  .on_mouse_down(move |cx, _| {
+                    cx.emit(PresetEvent::Load(index));
+                    cx.emit(PopupEvent::Close);
+                  });
+              }
+            })
+            .height(Auto);
+          });
+        },
+      )
+      .width(Stretch(1.0));
+      preset_button(cx, ">", PresetEvent::Next);
+
+      Textbox::new(cx, UiData::preset_name)
+        .on_mouse_down(|cx, _| {
+          cx.emit(TextEvent::StartEdit);
+        })
+        .on_submit(|cx, text, _| {
+          cx.emit(TextEvent::EndEdit);
+          cx.emit(PresetEvent::SetName(text));
+        })
+        .class("preset-name-input");
+      preset_button(cx, "Save", PresetEvent::Save);
+      preset_button(cx, "Rename", PresetEvent::Rename);
+      preset_button(cx, "Delete", PresetEvent::Delete);
+
+      for (slot, name) in ["A", "B"].into_iter().enumerate() {
+        preset_button(cx, name, PresetEvent::SelectSlot(slot)).toggle_class(
+          "active",
+          UiData::ab_slot.map(move |ab_slot| *ab_slot == slot),
+        );
+      }
+      preset_button(cx, "A>B", PresetEvent::CopyAToB);
+    })
+    .height(Pixels(20.0))
+    .col_between(Pixels(4.0))
+    .child_top(Stretch(1.0))
+    .child_bottom(Stretch(1.0))
+    .font_size(11.0)
+  }
+}
@@ -0,0 +1,133 @@
+use crate::reverb_parameters::ReverbParameters;
+use nih_plug_vizia::vizia::{
+  binding::{Binding, Lens},
+  prelude::{Canvas, Context, Data, DrawContext, LayoutModifiers, LensExt, Units::Stretch},
+  vg,
+  view::{Handle, View},
+  views::ZStack,
+};
+use reverb::{get_frequency_response, EqBands};
+use std::sync::Arc;
+
+const MIN_FREQ: f32 = 20.;
+const MAX_FREQ: f32 = 20000.;
+const DB_RANGE: f32 = 24.;
+const POINTS: usize = 128;
+
+#[derive(Clone, Copy, Data)]
+struct ResponseParams {
+  tilt: f32,
+  eq: bool,
+  low_shelf_freq: f32,
+  low_shelf_gain: f32,
+  peak_freq: f32,
+  peak_gain: f32,
+  peak_q: f32,
+  high_shelf_freq: f32,
+  high_shelf_gain: f32,
+}
+
+impl ResponseParams {
+  fn new(params: &ReverbParameters) -> Self {
+    Self {
+      tilt: params.tilt.value(),
+      eq: params.eq.value(),
+      low_shelf_freq: params.low_shelf_freq.value(),
+      low_shelf_gain: params.low_shelf_gain.value(),
+      peak_freq: params.peak_freq.value(),
+      peak_gain: params.peak_gain.value(),
+      peak_q: params.peak_q.value(),
+      high_shelf_freq: params.high_shelf_freq.value(),
+      high_shelf_gain: params.high_shelf_gain.value(),
+    }
+  }
+
+  fn get_eq(&self) -> Option<EqBands> {
+    if self.eq {
+      Some((
+        (self.low_shelf_freq, self.low_shelf_gain),
+        (self.peak_freq, self.peak_gain, self.peak_q),
+        (self.high_shelf_freq, self.high_shelf_gain),
+      ))
+    } else {
+      None
+    }
+  }
+}
+
+pub struct ResponseCurve {
+  magnitudes: Vec<f32>,
+}
+
+impl ResponseCurve {
+  /// Draws the magnitude response of the tilt filter and the output eq on a logarithmic frequency axis. The
+  /// curve is recomputed whenever one of their params changes.
+  pub fn new<L>(cx: &mut Context, lens: L) -> Handle<ZStack>
+  where
+    L: 'static + Lens<Target = Arc<ReverbParameters>> + Copy + Send + Sync,
+    <L as Lens>::Source: 'static,
+  {
+    ZStack::new(cx, |cx| {
+      Binding::new(
+        cx,
+        lens.map(|params| ResponseParams::new(params)),
+        |cx, response_params| {
+          let response_params = response_params.get(cx);
+          let eq = response_params.get_eq();
+          let magnitudes = (0..POINTS)
+            .map(|i| {
+              let freq = MIN_FREQ * (MAX_FREQ / MIN_FREQ).powf(i as f32 / (POINTS - 1) as f32);
+              get_frequency_response(freq, response_params.tilt, eq)
+            })
+            .collect();
+
+          Self { magnitudes }
+            .build(cx, |_| {})
+            .width(Stretch(1.0))
+            .height(Stretch(1.0));
+        },
+      );
+    })
+  }
+}
+
+impl View for ResponseCurve {
+  fn element(&self) -> Option<&'static str> {
+    Some("response-curve")
+  }
+
+  fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+    let bounds = cx.bounds();
+    let center = bounds.y + bounds.h * 0.5;
+
+    let mut background = vg::Path::new();
+    background.rect(bounds.x, bounds.y, bounds.w, bounds.h);
+    canvas.fill_path(
+      &mut background,
+      &vg::Paint::color(vg::Color::rgb(54, 54, 54)),
+    );
+
+    let mut zero_line = vg::Path::new();
+    zero_line.move_to(bounds.x, center);
+    zero_line.line_to(bounds.x + bounds.w, center);
+    canvas.stroke_path(
+      &mut zero_line,
+      &vg::Paint::color(vg::Color::rgb(80, 80, 80)).with_line_width(1.),
+    );
+
+    let mut curve = vg::Path::new();
+    for (i, magnitude) in self.magnitudes.iter().enumerate() {
+      let x = bounds.x + bounds.w * i as f32 / (POINTS - 1) as f32;
+      let y = center - magnitude.clamp(-DB_RANGE, DB_RANGE) / DB_RANGE * bounds.h * 0.5;
+      if i == 0 {
+        curve.move_to(x, y);
+      } else {
+        curve.line_to(x, y);
+      }
+    }
+    canvas.stroke_path(
+      &mut curve,
+      &vg::Paint::color(vg::Color::rgb(255, 180, 51)).with_line_width(1.5),
+    );
+  }
+}
@@ -0,0 +1,25 @@
+use nih_plug_vizia::vizia::{
+  prelude::{ActionModifiers, Context, LayoutModifiers, StyleModifiers, Units::Pixels},
+  view::Handle,
+  views::{HStack, Label},
+};
+
+const SCALE_FACTORS: [f64; 4] = [1., 1.25, 1.5, 2.];
+
+pub struct ScaleSelector {}
+
+impl ScaleSelector {
+  /// Sets the scale factor of the whole editor, on top of the size that the size handle sets. The editor state
+  /// stores it with the session.
+  pub fn new(cx: &mut Context) -> Handle<HStack> {
+    HStack::new(cx, |cx| {
+      for scale_factor in SCALE_FACTORS {
+        Label::new(cx, &format!("{}%", (scale_factor * 100.) as u32))
+          .class("scale-button")
+          .on_mouse_down(move |cx, _| cx.set_user_scale_factor(scale_factor));
+      }
+    })
+    .height(Pixels(20.0))
+    .col_between(Pixels(4.0))
+  }
+}
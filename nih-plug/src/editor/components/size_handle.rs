@@ -0,0 +1,83 @@
+use crate::editor::DEFAULT_SIZE;
+use nih_plug_vizia::{
+  vizia::{
+    prelude::{Canvas, Context, DrawContext, Event, EventContext, MouseButton, WindowEvent},
+    vg,
+    view::{Handle, View},
+  },
+  widgets::GuiContextEvent,
+};
+use std::sync::{Arc, RwLock};
+
+pub struct SizeHandle {
+  editor_size: Arc<RwLock<(u32, u32)>>,
+  // The cursor position and editor size when the drag started
+  drag_start: Option<((f32, f32), (u32, u32))>,
+}
+
+impl SizeHandle {
+  /// Resizes the editor freely by dragging the bottom right corner, down to the default size. The size is
+  /// stored with the session and the scale factor of the editor applies on top of it.
+  pub fn new(cx: &mut Context, editor_size: Arc<RwLock<(u32, u32)>>) -> Handle<Self> {
+    Self {
+      editor_size,
+      drag_start: None,
+    }
+    .build(cx, |_| {})
+  }
+
+  fn get_size((width, height): (u32, u32), (x, y): (f32, f32), scale_factor: f32) -> (u32, u32) {
+    // The cursor moves in physical pixels, while the editor size is in logical pixels before scaling
+    (
+      (width as f32 + x / scale_factor).max(DEFAULT_SIZE.0 as f32) as u32,
+      (height as f32 + y / scale_factor).max(DEFAULT_SIZE.1 as f32) as u32,
+    )
+  }
+}
+
+impl View for SizeHandle {
+  fn element(&self) -> Option<&'static str> {
+    Some("size-handle")
+  }
+
+  fn event(&mut self, cx: &mut EventContext, event: &mut Event) {
+    event.map(|window_event, meta| match *window_event {
+      WindowEvent::MouseDown(MouseButton::Left) => {
+        cx.capture();
+        self.drag_start = Some((
+          (cx.mouse().cursorx, cx.mouse().cursory),
+          *self.editor_size.read().unwrap(),
+        ));
+        meta.consume();
+      }
+      WindowEvent::MouseUp(MouseButton::Left) => {
+        if self.drag_start.take().is_some() {
+          cx.release();
+          meta.consume();
+        }
+      }
+      WindowEvent::MouseMove(x, y) => {
+        if let Some(((start_x, start_y), start_size)) = self.drag_start {
+          let size = Self::get_size(start_size, (x - start_x, y - start_y), cx.scale_factor());
+          if size != *self.editor_size.read().unwrap() {
+            *self.editor_size.write().unwrap() = size;
+            // Resizes the window to the size that the editor state reports
+            cx.emit(GuiContextEvent::Resize);
+          }
+        }
+      }
+      _ => {}
+    });
+  }
+
+  fn draw(&self, cx: &mut DrawContext, canvas: &mut Canvas) {
+    let bounds = cx.bounds();
+
+    let mut triangle = vg::Path::new();
+    triangle.move_to(bounds.x + bounds.w, bounds.y);
+    triangle.line_to(bounds.x + bounds.w, bounds.y + bounds.h);
+    triangle.line_to(bounds.x, bounds.y + bounds.h);
+    triangle.close();
+    canvas.fill_path(&mut triangle, &vg::Paint::color(vg::Color::rgb(54, 54, 54)));
+  }
+}
@@ -1,5 +1,13 @@
-use crate::reverb_parameters::ReverbParameters;
-use nih_plug::prelude::{GuiContext, ParamPtr};
+use crate::{
+  decay_preview::DecayPreview,
+  meters::Meters,
+  presets::{self, Preset, PresetValues},
+  reverb_parameters::ReverbParameters,
+};
+use nih_plug::{
+  nih_error,
+  prelude::{GuiContext, ParamPtr},
+};
 use nih_plug_vizia::vizia::prelude::*;
 use std::sync::Arc;
 
@@ -7,10 +15,189 @@ pub enum ParamChangeEvent {
   SetParam(ParamPtr, f32),
 }
 
+#[derive(Clone)]
+pub enum PresetEvent {
+  Load(usize),
+  Previous,
+  Next,
+  SetName(String),
+  Save,
+  Rename,
+  Delete,
+  SelectSlot(usize),
+  CopyAToB,
+}
+
 #[derive(Lens)]
 pub struct UiData {
   pub params: Arc<ReverbParameters>,
+  pub meters: Arc<Meters>,
+  pub decay_preview: Arc<DecayPreview>,
   pub gui_context: Arc<dyn GuiContext>,
+  pub presets: Vec<Preset>,
+  pub preset_names: Vec<String>,
+  pub preset_index: Option<usize>,
+  pub preset_name: String,
+  pub ab_slots: [Option<PresetValues>; 2],
+  pub ab_slot: usize,
+}
+
+impl UiData {
+  pub fn new(
+    params: Arc<ReverbParameters>,
+    meters: Arc<Meters>,
+    decay_preview: Arc<DecayPreview>,
+    gui_context: Arc<dyn GuiContext>,
+  ) -> Self {
+    let mut ui_data = Self {
+      params,
+      meters,
+      decay_preview,
+      gui_context,
+      presets: Vec::new(),
+      preset_names: Vec::new(),
+      preset_index: None,
+      preset_name: String::new(),
+      ab_slots: [None, None],
+      ab_slot: 0,
+    };
+    ui_data.refresh_presets();
+    ui_data
+  }
+
+  fn refresh_presets(&mut self) {
+    self.presets = presets::get_presets(&self.params);
+    self.preset_names = self
+      .presets
+      .iter()
+      .map(|preset| preset.name.clone())
+      .collect();
+  }
+
+  fn get_user_preset_index(&self, name: &str) -> Option<usize> {
+    self
+      .presets
+      .iter()
+      .position(|preset| !preset.is_factory && preset.name == name)
+  }
+
+  fn get_selected_user_preset(&self) -> Option<&Preset> {
+    self
+      .preset_index
+      .and_then(|index| self.presets.get(index))
+      .filter(|preset| !preset.is_factory)
+  }
+
+  /// Sets the params through the host, so automation recording and undo keep working.
+  fn load_values(&self, values: &[(String, f32)]) {
+    for (param_ptr, value) in presets::get_param_values(&self.params, values) {
+      unsafe {
+        self.gui_context.raw_begin_set_parameter(param_ptr);
+        self
+          .gui_context
+          .raw_set_parameter_normalized(param_ptr, value);
+        self.gui_context.raw_end_set_parameter(param_ptr);
+      }
+    }
+  }
+
+  fn load_preset(&mut self, index: usize) {
+    if let Some(preset) = self.presets.get(index) {
+      self.load_values(&preset.values);
+      self.preset_name = preset.name.clone();
+      self.preset_index = Some(index);
+    }
+  }
+
+  fn select_slot(&mut self, slot: usize) {
+    if slot == self.ab_slot {
+      return;
+    }
+    // The slot that's left behind remembers the current values, an empty slot starts as a copy of them
+    self.ab_slots[self.ab_slot] = Some(presets::get_values(&self.params));
+    if let Some(values) = self.ab_slots[slot].clone() {
+      self.load_values(&values);
+    }
+    self.ab_slot = slot;
+  }
+
+  fn copy_a_to_b(&mut self) {
+    let values = match self.ab_slot {
+      0 => presets::get_values(&self.params),
+      _ => match self.ab_slots[0].clone() {
+        Some(values) => values,
+        None => return,
+      },
+    };
+    if self.ab_slot == 1 {
+      self.load_values(&values);
+    }
+    self.ab_slots[1] = Some(values);
+  }
+
+  fn handle_preset_event(&mut self, event: &PresetEvent) {
+    match event {
+      PresetEvent::Load(index) => self.load_preset(*index),
+      PresetEvent::Previous | PresetEvent::Next => {
+        let length = self.presets.len();
+        if length == 0 {
+          return;
+        }
+        let index = match (self.preset_index, event) {
+          (Some(index), PresetEvent::Previous) => (index + length - 1) % length,
+          (Some(index), _) => (index + 1) % length,
+          (None, _) => 0,
+        };
+        self.load_preset(index);
+      }
+      PresetEvent::SetName(name) => self.preset_name = name.trim().to_string(),
+      PresetEvent::Save => {
+        let values = presets::get_values(&self.params);
+        // Saving under the name of the loaded user preset updates it, any other name has to be new
+        let result = match self.get_selected_user_preset() {
+          Some(preset) if preset.name == self.preset_name => {
+            presets::update_user_preset(&self.preset_name, &values)
+          }
+          _ => presets::save_user_preset(&self.preset_name, &values),
+        };
+        match result {
+          Ok(()) => {
+            self.refresh_presets();
+            self.preset_index = self.get_user_preset_index(&self.preset_name);
+          }
+          Err(err) => nih_error!("Failed to save preset {}: {}", self.preset_name, err),
+        }
+      }
+      PresetEvent::Rename => {
+        let name = match self.get_selected_user_preset() {
+          Some(preset) => preset.name.clone(),
+          None => return,
+        };
+        match presets::rename_user_preset(&name, &self.preset_name) {
+          Ok(()) => {
+            self.refresh_presets();
+            self.preset_index = self.get_user_preset_index(&self.preset_name);
+          }
+          Err(err) => nih_error!("Failed to rename preset {}: {}", name, err),
+        }
+      }
+      PresetEvent::Delete => {
+        let name = match self.get_selected_user_preset() {
+          Some(preset) => preset.name.clone(),
+          None => return,
+        };
+        match presets::delete_user_preset(&name) {
+          Ok(()) => {
+            self.refresh_presets();
+            self.preset_index = None;
+          }
+          Err(err) => nih_error!("Failed to delete preset {}: {}", name, err),
+        }
+      }
+      PresetEvent::SelectSlot(slot) => self.select_slot(*slot),
+      PresetEvent::CopyAToB => self.copy_a_to_b(),
+    }
+  }
 }
 
 impl Model for UiData {
@@ -24,5 +211,6 @@ impl Model for UiData {
         };
       }
     });
+    event.map(|preset_event, _| self.handle_preset_event(preset_event));
   }
 }
@@ -1,14 +1,43 @@
-use nih_plug::prelude::*;
-use reverb::{Params as ProcessParams, Reverb};
+use nih_plug::{
+  prelude::*,
+  wrapper::state::{ParamValue, PluginState},
+};
+use reverb::{Params as ProcessParams, Reverb, RoomGeometry, RoomReflections};
 mod reverb_parameters;
 use reverb_parameters::ReverbParameters;
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+mod decay_preview;
 mod editor;
+mod meters;
+mod presets;
+use decay_preview::{DecayPreview, DecayPreviewRenderer};
+use meters::{MeterProcessor, Meters};
+
+// How often the background task checks whether the params have settled, so the decay preview can be rendered
+const DECAY_PREVIEW_INTERVAL: f32 = 0.25;
+// How long to wait for the room reflections of a geometry before the task is sent again
+const ROOM_REFLECTIONS_RETRY_INTERVAL: f32 = 0.1;
+
+pub enum Task {
+  UpdateRoomReflections(RoomGeometry),
+  UpdateDecayPreview(f32),
+}
 
 struct DmReverb {
   params: Arc<ReverbParameters>,
   reverb: Reverb,
   process_params: ProcessParams,
+  room_geometry: Option<RoomGeometry>,
+  pending_room_geometry: Option<RoomGeometry>,
+  room_reflections: Arc<Mutex<Option<(RoomGeometry, RoomReflections)>>>,
+  room_reflections_retry_interval: usize,
+  room_reflections_countdown: usize,
+  meters: Arc<Meters>,
+  meter_processor: MeterProcessor,
+  decay_preview: Arc<DecayPreview>,
+  decay_preview_renderer: Arc<Mutex<DecayPreviewRenderer>>,
+  decay_preview_interval: usize,
+  decay_preview_countdown: usize,
 }
 
 impl Default for DmReverb {
@@ -18,6 +47,66 @@ impl Default for DmReverb {
       params: params.clone(),
       reverb: Reverb::new(44100.),
       process_params: ProcessParams::new(44100.),
+      room_geometry: None,
+      pending_room_geometry: None,
+      room_reflections: Arc::new(Mutex::new(None)),
+      room_reflections_retry_interval: (44100. * ROOM_REFLECTIONS_RETRY_INTERVAL) as usize,
+      room_reflections_countdown: 0,
+      meters: Arc::new(Meters::default()),
+      meter_processor: MeterProcessor::new(44100.),
+      decay_preview: Arc::new(DecayPreview::default()),
+      decay_preview_renderer: Arc::new(Mutex::new(DecayPreviewRenderer::default())),
+      decay_preview_interval: (44100. * DECAY_PREVIEW_INTERVAL) as usize,
+      decay_preview_countdown: 0,
+    }
+  }
+}
+
+impl DmReverb {
+  fn update_room_reflections(
+    &mut self,
+    buffer_size: usize,
+    context: &mut impl ProcessContext<Self>,
+  ) {
+    let room_geometry = self.params.get_room_geometry();
+    if self.room_geometry != Some(room_geometry) {
+      self.room_geometry = Some(room_geometry);
+      self.pending_room_geometry = Some(room_geometry);
+      self.room_reflections_countdown = 0;
+    }
+
+    if let Ok(mut room_reflections) = self.room_reflections.try_lock() {
+      if let Some((room_geometry, room_reflections)) = room_reflections.take() {
+        self.reverb.set_room_reflections(room_reflections);
+        if self.pending_room_geometry == Some(room_geometry) {
+          self.pending_room_geometry = None;
+        }
+      }
+    }
+
+    // The host drops background tasks when its queue is full, so the task is sent again until the reflections
+    // of the pending geometry are published
+    if let Some(room_geometry) = self.pending_room_geometry {
+      if buffer_size < self.room_reflections_countdown {
+        self.room_reflections_countdown -= buffer_size;
+      } else {
+        self.room_reflections_countdown = self.room_reflections_retry_interval;
+        context.execute_background(Task::UpdateRoomReflections(room_geometry));
+      }
+    }
+  }
+
+  fn update_decay_preview(
+    &mut self,
+    buffer_size: usize,
+    tempo: f32,
+    context: &mut impl ProcessContext<Self>,
+  ) {
+    if buffer_size < self.decay_preview_countdown {
+      self.decay_preview_countdown -= buffer_size;
+    } else {
+      self.decay_preview_countdown = self.decay_preview_interval;
+      context.execute_background(Task::UpdateDecayPreview(tempo));
     }
   }
 }
@@ -29,26 +118,78 @@ impl Plugin for DmReverb {
   const EMAIL: &'static str = "davemollen@gmail.com";
   const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
-  const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[AudioIOLayout {
-    main_input_channels: NonZeroU32::new(2),
-    main_output_channels: NonZeroU32::new(2),
-    ..AudioIOLayout::const_default()
-  }];
+  const AUDIO_IO_LAYOUTS: &'static [AudioIOLayout] = &[
+    AudioIOLayout {
+      main_input_channels: NonZeroU32::new(2),
+      main_output_channels: NonZeroU32::new(2),
+      ..AudioIOLayout::const_default()
+    },
+    AudioIOLayout {
+      main_input_channels: NonZeroU32::new(2),
+      main_output_channels: NonZeroU32::new(2),
+      aux_output_ports: &[new_nonzero_u32(2); 3],
+      names: PortNames {
+        layout: Some("Stereo with early, late and dry outputs"),
+        aux_outputs: &["Early", "Late", "Dry"],
+        ..PortNames::const_default()
+      },
+      ..AudioIOLayout::const_default()
+    },
+  ];
   const MIDI_INPUT: MidiConfig = MidiConfig::None;
   const SAMPLE_ACCURATE_AUTOMATION: bool = true;
 
-  // More advanced plugins can use this to run expensive background tasks. See the field's
-  // documentation for more information. `()` means that the plugin does not have any background
-  // tasks.
-  type BackgroundTask = ();
+  // The image-source early reflections are computed in a background task whenever the room
+  // geometry changes. While the editor is open, the decay preview is rendered in the background
+  // too.
+  type BackgroundTask = Task;
   type SysExMessage = ();
 
   fn params(&self) -> Arc<dyn Params> {
     self.params.clone()
   }
 
+  // Reverse used to be a toggle with the same id, so a restored session can still contain a boolean. nih-plug
+  // only restores a boolean into a BoolParam, so it's converted to 0 or 100 % here. The skipped params are removed,
+  // which makes nih-plug keep their current values.
+  fn filter_state(state: &mut PluginState) {
+    for id in presets::SKIPPED_PARAMS {
+      state.params.remove(id);
+    }
+    if let Some(reverse) = state.params.get_mut("reverse") {
+      if let ParamValue::Bool(is_reversed) = *reverse {
+        *reverse = ParamValue::F32(if is_reversed { 1. } else { 0. });
+      }
+    }
+  }
+
+  fn task_executor(&mut self) -> TaskExecutor<Self> {
+    let room_reflections = self.room_reflections.clone();
+    let params = self.params.clone();
+    let decay_preview = self.decay_preview.clone();
+    let decay_preview_renderer = self.decay_preview_renderer.clone();
+
+    Box::new(move |task| match task {
+      Task::UpdateRoomReflections(room_geometry) => {
+        *room_reflections.lock().unwrap() =
+          Some((room_geometry, room_geometry.compute_reflections()));
+      }
+      Task::UpdateDecayPreview(tempo) => {
+        decay_preview_renderer
+          .lock()
+          .unwrap()
+          .update(&params, tempo, &decay_preview);
+      }
+    })
+  }
+
   fn editor(&mut self, _async_executor: AsyncExecutor<Self>) -> Option<Box<dyn Editor>> {
-    editor::create(self.params.clone(), self.params.editor_state.clone())
+    editor::create(
+      self.params.clone(),
+      self.meters.clone(),
+      self.decay_preview.clone(),
+      self.params.editor_state.clone(),
+    )
   }
 
   fn initialize(
@@ -59,37 +200,90 @@ impl Plugin for DmReverb {
   ) -> bool {
     self.reverb = Reverb::new(buffer_config.sample_rate);
     self.process_params = ProcessParams::new(buffer_config.sample_rate);
+    self.meter_processor = MeterProcessor::new(buffer_config.sample_rate);
+    self.decay_preview_interval = (buffer_config.sample_rate * DECAY_PREVIEW_INTERVAL) as usize;
+    self.room_reflections_retry_interval =
+      (buffer_config.sample_rate * ROOM_REFLECTIONS_RETRY_INTERVAL) as usize;
+    let room_geometry = self.params.get_room_geometry();
+    self
+      .reverb
+      .set_room_reflections(room_geometry.compute_reflections());
+    self.room_geometry = Some(room_geometry);
+    self.pending_room_geometry = None;
     true
   }
 
+  // Called before processing starts and when the host stops or seeks, so renders start from the same state
+  fn reset(&mut self) {
+    self.reverb.reset();
+  }
+
   fn process(
     &mut self,
     buffer: &mut Buffer,
-    _aux: &mut AuxiliaryBuffers,
-    _context: &mut impl ProcessContext<Self>,
+    aux: &mut AuxiliaryBuffers,
+    context: &mut impl ProcessContext<Self>,
   ) -> ProcessStatus {
-    self.process_params.set(
-      if self.params.reverse.value() { 1. } else { 0. },
-      self.params.predelay.value(),
-      self.params.size.value(),
-      self.params.speed.value(),
-      self.params.depth.value(),
-      self.params.absorb.value(),
-      self.params.decay.value(),
-      self.params.tilt.value(),
-      self.params.shimmer.value(),
-      self.params.mix.value(),
-    );
-
-    buffer.iter_samples().for_each(|mut channel_samples| {
-      let channel_iterator = &mut channel_samples.iter_mut();
-      let left_channel = channel_iterator.next().unwrap();
-      let right_channel = channel_iterator.next().unwrap();
-
-      (*left_channel, *right_channel) = self
-        .reverb
-        .process((*left_channel, *right_channel), &mut self.process_params);
-    });
+    self.update_room_reflections(buffer.samples(), context);
+    let tempo = context.transport().tempo.unwrap_or(120.) as f32;
+    self
+      .params
+      .set_process_params(&mut self.process_params, tempo);
+
+    // Metering and the decay preview only run while somebody is looking at them
+    let is_metering = self.params.editor_state.is_open();
+    if is_metering {
+      self.update_decay_preview(buffer.samples(), tempo, context);
+    }
+
+    if let [early_buffer, late_buffer, dry_buffer] = &mut aux.outputs[..] {
+      let early_outputs = early_buffer.as_slice();
+      let late_outputs = late_buffer.as_slice();
+      let dry_outputs = dry_buffer.as_slice();
+
+      buffer
+        .iter_samples()
+        .enumerate()
+        .for_each(|(i, mut channel_samples)| {
+          let channel_iterator = &mut channel_samples.iter_mut();
+          let left_channel = channel_iterator.next().unwrap();
+          let right_channel = channel_iterator.next().unwrap();
+          let input = (*left_channel, *right_channel);
+
+          let (output, early, late) = self
+            .reverb
+            .process_with_buses(input, &mut self.process_params);
+          (*left_channel, *right_channel) = output;
+          if is_metering {
+            self
+              .meter_processor
+              .process(input, self.reverb.get_wet(), output);
+          }
+          (early_outputs[0][i], early_outputs[1][i]) = early;
+          (late_outputs[0][i], late_outputs[1][i]) = late;
+          (dry_outputs[0][i], dry_outputs[1][i]) = input;
+        });
+    } else {
+      buffer.iter_samples().for_each(|mut channel_samples| {
+        let channel_iterator = &mut channel_samples.iter_mut();
+        let left_channel = channel_iterator.next().unwrap();
+        let right_channel = channel_iterator.next().unwrap();
+        let input = (*left_channel, *right_channel);
+
+        let output = self.reverb.process(input, &mut self.process_params);
+        (*left_channel, *right_channel) = output;
+        if is_metering {
+          self
+            .meter_processor
+            .process(input, self.reverb.get_wet(), output);
+        }
+      });
+    }
+    if is_metering {
+      self
+        .meter_processor
+        .publish(&self.meters, self.reverb.get_tank_levels());
+    }
     ProcessStatus::Normal
   }
 
@@ -121,3 +315,56 @@ impl Vst3Plugin for DmReverb {
 
 nih_export_clap!(DmReverb);
 nih_export_vst3!(DmReverb);
+
+#[cfg(test)]
+mod tests {
+  use super::DmReverb;
+  use nih_plug::{
+    prelude::Plugin,
+    wrapper::state::{ParamValue, PluginState},
+  };
+  use std::collections::BTreeMap;
+
+  fn filter_params(params: Vec<(&str, ParamValue)>) -> BTreeMap<String, ParamValue> {
+    let mut state = PluginState {
+      version: String::from("0.1.0"),
+      params: params
+        .into_iter()
+        .map(|(id, value)| (id.to_string(), value))
+        .collect(),
+      fields: BTreeMap::new(),
+    };
+    DmReverb::filter_state(&mut state);
+    state.params
+  }
+
+  fn filter_reverse(reverse: ParamValue) -> Option<ParamValue> {
+    filter_params(vec![("reverse", reverse)]).remove("reverse")
+  }
+
+  #[test]
+  fn should_restore_the_former_reverse_toggle_as_a_float() {
+    assert!(matches!(
+      filter_reverse(ParamValue::Bool(true)),
+      Some(ParamValue::F32(reverse)) if reverse == 1.
+    ));
+    assert!(matches!(
+      filter_reverse(ParamValue::Bool(false)),
+      Some(ParamValue::F32(reverse)) if reverse == 0.
+    ));
+    assert!(matches!(
+      filter_reverse(ParamValue::F32(0.4)),
+      Some(ParamValue::F32(reverse)) if reverse == 0.4
+    ));
+  }
+
+  #[test]
+  fn should_keep_kill_dry_when_restoring_a_state() {
+    let params = filter_params(vec![
+      ("kill_dry", ParamValue::Bool(true)),
+      ("mix", ParamValue::F32(0.4)),
+    ]);
+    assert!(!params.contains_key("kill_dry"));
+    assert!(params.contains_key("mix"));
+  }
+}
@@ -0,0 +1,148 @@
+use nih_plug::prelude::AtomicF32;
+use std::sync::atomic::Ordering;
+
+const PEAK_DECAY_TIME: f32 = 150.;
+const RMS_TIME: f32 = 300.;
+
+#[derive(Default)]
+pub struct Level {
+  pub peak: AtomicF32,
+  pub rms: AtomicF32,
+}
+
+/// Levels the audio thread publishes for the editor. Every value is a linear gain.
+pub struct Meters {
+  pub input: [Level; 2],
+  pub wet: [Level; 2],
+  pub output: [Level; 2],
+  pub tank_level: AtomicF32,
+  pub gain_compensation: AtomicF32,
+}
+
+impl Default for Meters {
+  fn default() -> Self {
+    Self {
+      input: Default::default(),
+      wet: Default::default(),
+      output: Default::default(),
+      tank_level: AtomicF32::new(0.),
+      gain_compensation: AtomicF32::new(1.),
+    }
+  }
+}
+
+#[derive(Clone, Copy)]
+struct LevelMeter {
+  peak: f32,
+  mean_square: f32,
+}
+
+impl LevelMeter {
+  fn new() -> Self {
+    Self {
+      peak: 0.,
+      mean_square: 0.,
+    }
+  }
+
+  fn process(&mut self, input: f32, peak_decay: f32, rms_coefficient: f32) {
+    let amplitude = input.abs();
+    self.peak = if amplitude > self.peak {
+      amplitude
+    } else {
+      self.peak * peak_decay + amplitude * (1. - peak_decay)
+    };
+    self.mean_square = input * input * (1. - rms_coefficient) + self.mean_square * rms_coefficient;
+  }
+
+  fn publish(&self, level: &Level) {
+    level.peak.store(self.peak, Ordering::Relaxed);
+    level.rms.store(self.mean_square.sqrt(), Ordering::Relaxed);
+  }
+}
+
+pub struct MeterProcessor {
+  input: [LevelMeter; 2],
+  wet: [LevelMeter; 2],
+  output: [LevelMeter; 2],
+  peak_decay: f32,
+  rms_coefficient: f32,
+}
+
+impl MeterProcessor {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      input: [LevelMeter::new(); 2],
+      wet: [LevelMeter::new(); 2],
+      output: [LevelMeter::new(); 2],
+      // The peak falls back by 12 dB over the decay time
+      peak_decay: 0.25_f32.powf((sample_rate * PEAK_DECAY_TIME / 1000.).recip()),
+      rms_coefficient: (-(sample_rate * RMS_TIME / 1000.).recip()).exp(),
+    }
+  }
+
+  pub fn process(&mut self, input: (f32, f32), wet: (f32, f32), output: (f32, f32)) {
+    let (peak_decay, rms_coefficient) = (self.peak_decay, self.rms_coefficient);
+    for (meters, (left, right)) in [
+      (&mut self.input, input),
+      (&mut self.wet, wet),
+      (&mut self.output, output),
+    ] {
+      meters[0].process(left, peak_decay, rms_coefficient);
+      meters[1].process(right, peak_decay, rms_coefficient);
+    }
+  }
+
+  pub fn publish(&self, meters: &Meters, (tank_level, gain_compensation): (f32, f32)) {
+    for (level_meters, levels) in [
+      (&self.input, &meters.input),
+      (&self.wet, &meters.wet),
+      (&self.output, &meters.output),
+    ] {
+      level_meters[0].publish(&levels[0]);
+      level_meters[1].publish(&levels[1]);
+    }
+    meters.tank_level.store(tank_level, Ordering::Relaxed);
+    meters
+      .gain_compensation
+      .store(gain_compensation, Ordering::Relaxed);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{MeterProcessor, Meters, PEAK_DECAY_TIME};
+  use std::{
+    f32::consts::{FRAC_1_SQRT_2, TAU},
+    sync::atomic::Ordering,
+  };
+
+  fn get_levels(
+    meter_processor: &mut MeterProcessor,
+    input: impl Iterator<Item = f32>,
+  ) -> (f32, f32) {
+    input.for_each(|x| meter_processor.process((x, x), (x, x), (x, x)));
+    let meters = Meters::default();
+    meter_processor.publish(&meters, (0., 1.));
+    (
+      meters.input[0].peak.load(Ordering::Relaxed),
+      meters.input[0].rms.load(Ordering::Relaxed),
+    )
+  }
+
+  #[test]
+  fn should_settle_on_the_rms_of_a_sine_and_let_the_peak_fall() {
+    let sample_rate = 44100.;
+    let mut meter_processor = MeterProcessor::new(sample_rate);
+
+    let sine = (0..sample_rate as usize * 2).map(|i| (i as f32 * 1000. * TAU / sample_rate).sin());
+    let (peak, rms) = get_levels(&mut meter_processor, sine);
+    assert!((peak - 1.).abs() < 0.01);
+    assert!((rms - FRAC_1_SQRT_2).abs() < 0.01);
+
+    // The peak falls by 12 dB over the decay time
+    let silence = (0..(sample_rate * PEAK_DECAY_TIME / 1000.) as usize).map(|_| 0.);
+    let (decayed_peak, _) = get_levels(&mut meter_processor, silence);
+    assert!((decayed_peak / peak - 0.25).abs() < 0.01);
+  }
+}
@@ -0,0 +1,343 @@
+use crate::reverb_parameters::{EarlyReflections, ReverbParameters};
+use nih_plug::prelude::{ParamPtr, Params};
+use std::{
+  env, fs, io,
+  path::{Path, PathBuf},
+};
+
+const EXTENSION: &str = "preset";
+// These params belong to the session rather than to the sound, so loading a preset or restoring a state leaves
+// them alone
+pub const SKIPPED_PARAMS: [&str; 1] = ["kill_dry"];
+
+// Plain values per param id, where enum params take the index of their variant. Params that aren't listed are set
+// to their default.
+const FACTORY_PRESETS: &[(&str, &[(&str, f32)])] = &[
+  ("Default", &[]),
+  (
+    "Small Room",
+    &[
+      ("size", 20.),
+      ("decay", 0.5),
+      ("absorb", 0.6),
+      ("early_level", 1.2),
+      ("mix", 0.3),
+    ],
+  ),
+  (
+    "Large Hall",
+    &[
+      ("size", 300.),
+      ("predelay", 40.),
+      ("decay", 0.95),
+      ("absorb", 0.4),
+      ("early", EarlyReflections::Hall as i32 as f32),
+      ("mix", 0.4),
+    ],
+  ),
+  (
+    "Dark Plate",
+    &[
+      ("size", 60.),
+      ("decay", 0.85),
+      ("tilt", -0.4),
+      ("depth", 0.2),
+      ("early", EarlyReflections::Plate as i32 as f32),
+    ],
+  ),
+  (
+    "Shimmer Pad",
+    &[
+      ("size", 250.),
+      ("decay", 1.),
+      ("shimmer", 0.6),
+      ("tilt", 0.2),
+      ("mix", 0.5),
+    ],
+  ),
+  (
+    "Reverse Swell",
+    &[
+      ("size", 120.),
+      ("reverse", 1.),
+      ("decay", 0.8),
+      ("mix", 0.5),
+    ],
+  ),
+];
+
+/// Normalized values per param id.
+pub type PresetValues = Vec<(String, f32)>;
+
+pub struct Preset {
+  pub name: String,
+  pub values: PresetValues,
+  pub is_factory: bool,
+}
+
+/// Returns the factory presets followed by the user presets, sorted by name.
+pub fn get_presets(params: &ReverbParameters) -> Vec<Preset> {
+  let mut user_presets = get_user_presets();
+  user_presets.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+  get_factory_presets(params)
+    .into_iter()
+    .chain(user_presets)
+    .collect()
+}
+
+pub fn get_values(params: &ReverbParameters) -> PresetValues {
+  params
+    .param_map()
+    .into_iter()
+    // The pointers stay valid for as long as `params` is borrowed
+    .map(|(id, param_ptr, _)| (id, unsafe { param_ptr.unmodulated_normalized_value() }))
+    .collect()
+}
+
+/// Pairs every param a preset sets with its normalized value in `values`, or with its default when `values`
+/// doesn't store it.
+pub fn get_param_values(
+  params: &ReverbParameters,
+  values: &[(String, f32)],
+) -> Vec<(ParamPtr, f32)> {
+  params
+    .param_map()
+    .into_iter()
+    .filter(|(id, _, _)| !SKIPPED_PARAMS.contains(&id.as_str()))
+    .map(|(id, param_ptr, _)| {
+      let value = values
+        .iter()
+        .find(|(value_id, _)| *value_id == id)
+        .map(|(_, value)| *value)
+        .unwrap_or_else(|| unsafe { param_ptr.default_normalized_value() });
+      (param_ptr, value)
+    })
+    .collect()
+}
+
+/// Saves a new user preset. Fails when a factory or user preset already has this name.
+pub fn save_user_preset(name: &str, values: &[(String, f32)]) -> io::Result<()> {
+  let path = get_new_user_preset_path(name)?;
+  fs::create_dir_all(path.parent().unwrap())?;
+  fs::write(path, format_values(values))
+}
+
+/// Overwrites the values of an existing user preset.
+pub fn update_user_preset(name: &str, values: &[(String, f32)]) -> io::Result<()> {
+  let path = get_user_preset_path(name)?;
+  if !path.is_file() {
+    return Err(io::Error::new(
+      io::ErrorKind::NotFound,
+      "preset doesn't exist",
+    ));
+  }
+  fs::write(path, format_values(values))
+}
+
+/// Renames a user preset. Fails when a factory or user preset already has the new name.
+pub fn rename_user_preset(name: &str, new_name: &str) -> io::Result<()> {
+  let new_path = get_new_user_preset_path(new_name)?;
+  fs::rename(get_user_preset_path(name)?, new_path)
+}
+
+pub fn delete_user_preset(name: &str) -> io::Result<()> {
+  fs::remove_file(get_user_preset_path(name)?)
+}
+
+fn get_factory_presets(params: &ReverbParameters) -> Vec<Preset> {
+  let param_map = params.param_map();
+  FACTORY_PRESETS
+    .iter()
+    .map(|(name, plain_values)| Preset {
+      name: name.to_string(),
+      values: plain_values
+        .iter()
+        .filter_map(|(id, plain_value)| {
+          param_map
+            .iter()
+            .find(|(param_id, _, _)| param_id == id)
+            .map(|(_, param_ptr, _)| {
+              (id.to_string(), unsafe {
+                param_ptr.preview_normalized(*plain_value)
+              })
+            })
+        })
+        .collect(),
+      is_factory: true,
+    })
+    .collect()
+}
+
+fn get_user_presets() -> Vec<Preset> {
+  let entries = match get_user_preset_dir().and_then(|dir| fs::read_dir(dir).ok()) {
+    Some(entries) => entries,
+    None => return Vec::new(),
+  };
+
+  entries
+    .filter_map(|entry| {
+      let path = entry.ok()?.path();
+      if path.extension()? != EXTENSION {
+        return None;
+      }
+      Some(Preset {
+        name: path.file_stem()?.to_str()?.to_string(),
+        values: read_values(&path).ok()?,
+        is_factory: false,
+      })
+    })
+    .collect()
+}
+
+fn read_values(path: &Path) -> io::Result<PresetValues> {
+  Ok(
+    fs::read_to_string(path)?
+      .lines()
+      .filter_map(|line| {
+        let (id, value) = line.split_once('=')?;
+        Some((id.trim().to_string(), value.trim().parse().ok()?))
+      })
+      .collect(),
+  )
+}
+
+fn format_values(values: &[(String, f32)]) -> String {
+  values
+    .iter()
+    .map(|(id, value)| format!("{}={}\n", id, value))
+    .collect()
+}
+
+/// Returns the path for a user preset that doesn't exist yet. A user preset can't take the name of a factory
+/// preset either, as both would show up in the preset list.
+fn get_new_user_preset_path(name: &str) -> io::Result<PathBuf> {
+  let is_factory_name = FACTORY_PRESETS
+    .iter()
+    .any(|(factory_name, _)| factory_name.eq_ignore_ascii_case(name.trim()));
+  if is_factory_name {
+    return Err(io::Error::new(
+      io::ErrorKind::AlreadyExists,
+      "a factory preset has this name",
+    ));
+  }
+  let path = get_user_preset_path(name)?;
+  if path.exists() {
+    return Err(io::Error::new(
+      io::ErrorKind::AlreadyExists,
+      "a preset with this name already exists",
+    ));
+  }
+  Ok(path)
+}
+
+fn get_user_preset_path(name: &str) -> io::Result<PathBuf> {
+  let name = name.trim();
+  if name.is_empty() || name.contains(['/', '\\', ':']) || name.starts_with('.') {
+    return Err(io::Error::new(
+      io::ErrorKind::InvalidInput,
+      "invalid preset name",
+    ));
+  }
+  get_user_preset_dir()
+    .map(|dir| dir.join(format!("{}.{}", name, EXTENSION)))
+    .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no user data directory"))
+}
+
+fn get_user_preset_dir() -> Option<PathBuf> {
+  let data_dir = if cfg!(target_os = "windows") {
+    env::var_os("APPDATA").map(PathBuf::from)
+  } else if cfg!(target_os = "macos") {
+    env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Application Support"))
+  } else {
+    env::var_os("XDG_DATA_HOME")
+      .map(PathBuf::from)
+      .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/share")))
+  };
+  data_dir.map(|dir| dir.join("dm-Reverb").join("presets"))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{
+    get_factory_presets, get_param_values, get_user_preset_path, read_values, rename_user_preset,
+    save_user_preset,
+  };
+  use crate::reverb_parameters::{EarlyReflections, ReverbParameters};
+  use nih_plug::prelude::{Param, Params};
+  use std::{env, fs, io};
+
+  #[test]
+  fn should_read_values_and_skip_invalid_lines() {
+    let path = env::temp_dir().join(format!("dm-reverb-{}.preset", std::process::id()));
+    fs::write(&path, "size=0.3\n decay = 0.5 \nmix\nabsorb=high\n").unwrap();
+    let values = read_values(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(
+      values.unwrap(),
+      vec![("size".to_string(), 0.3), ("decay".to_string(), 0.5)]
+    );
+  }
+
+  #[test]
+  fn should_reject_invalid_preset_names() {
+    for name in ["", "  ", "a/b", "a\\b", "a:b", ".hidden"] {
+      assert_eq!(
+        get_user_preset_path(name).unwrap_err().kind(),
+        io::ErrorKind::InvalidInput
+      );
+    }
+    if let Ok(path) = get_user_preset_path(" Pad ") {
+      assert!(path.ends_with("Pad.preset"));
+    }
+  }
+
+  #[test]
+  fn should_reject_factory_preset_names() {
+    assert_eq!(
+      save_user_preset("default", &[]).unwrap_err().kind(),
+      io::ErrorKind::AlreadyExists
+    );
+    assert_eq!(
+      rename_user_preset("Pad", " Large Hall").unwrap_err().kind(),
+      io::ErrorKind::AlreadyExists
+    );
+  }
+
+  #[test]
+  fn should_fall_back_to_defaults_and_skip_kill_dry() {
+    let params = ReverbParameters::default();
+    let param_values = get_param_values(&params, &[("size".to_string(), 0.3)]);
+    let get_value = |id: &str| {
+      let (_, param_ptr, _) = params
+        .param_map()
+        .into_iter()
+        .find(|(param_id, _, _)| param_id == id)
+        .unwrap();
+      param_values
+        .iter()
+        .find(|(value_ptr, _)| *value_ptr == param_ptr)
+        .map(|(_, value)| *value)
+    };
+
+    assert_eq!(get_value("size"), Some(0.3));
+    assert_eq!(
+      get_value("decay"),
+      Some(params.decay.default_normalized_value())
+    );
+    assert_eq!(get_value("kill_dry"), None);
+  }
+
+  #[test]
+  fn should_set_the_early_reflections_of_factory_presets() {
+    let params = ReverbParameters::default();
+    let presets = get_factory_presets(&params);
+    let get_early = |name: &str| {
+      let preset = presets.iter().find(|preset| preset.name == name).unwrap();
+      let (_, value) = preset.values.iter().find(|(id, _)| id == "early").unwrap();
+      params.early.preview_plain(*value)
+    };
+
+    assert!(get_early("Large Hall") == EarlyReflections::Hall);
+    assert!(get_early("Dark Plate") == EarlyReflections::Plate);
+  }
+}
@@ -1,27 +1,111 @@
 use nih_plug::{
   formatters::{s2v_f32_percentage, v2s_f32_percentage},
-  prelude::{BoolParam, FloatParam, FloatRange, Params},
+  prelude::{BoolParam, Enum, EnumParam, FloatParam, FloatRange, IntParam, IntRange, Params},
 };
 use nih_plug_vizia::ViziaState;
-use reverb::shared::constants::{MAX_PREDELAY, MAX_SIZE, MIN_PREDELAY, MIN_SIZE};
-use std::sync::Arc;
+use reverb::shared::constants::{
+  MAX_GRAIN_DENSITY, MAX_GRAIN_PITCH_JITTER, MAX_GRAIN_SIZE, MAX_PREDELAY, MAX_REVERSE_TAIL_LENGTH,
+  MAX_ROOM_DIMENSION, MAX_SIZE, MIN_GRAIN_DENSITY, MIN_GRAIN_SIZE, MIN_PREDELAY,
+  MIN_REVERSE_TAIL_LENGTH, MIN_ROOM_DIMENSION, MIN_SIZE,
+};
+use reverb::{Params as ProcessParams, RoomGeometry, Settings};
+use std::sync::{Arc, RwLock};
 mod custom_formatters;
 use crate::editor;
 use custom_formatters::v2s_f32_digits;
 
+#[derive(Enum, PartialEq)]
+pub enum Quality {
+  Eco,
+  Standard,
+  High,
+}
+
+#[derive(Enum, PartialEq)]
+pub enum EarlyReflections {
+  Room,
+  Hall,
+  Plate,
+  Chamber,
+  Geometry,
+  None,
+}
+
+#[derive(Enum, PartialEq)]
+pub enum ShimmerMode {
+  Classic,
+  #[name = "High Quality"]
+  HighQuality,
+}
+
+#[derive(Enum, PartialEq)]
+pub enum LfoShape {
+  Sine,
+  Triangle,
+  #[name = "Smooth Random"]
+  SmoothRandom,
+  Chaos,
+}
+
+#[derive(Enum, PartialEq)]
+pub enum GrainWindow {
+  Hann,
+  Tukey,
+  Trapezoid,
+}
+
+#[derive(Enum, PartialEq)]
+pub enum MixMode {
+  #[name = "Equal Power"]
+  EqualPower,
+  Linear,
+  #[name = "Dry/Wet"]
+  Independent,
+}
+
+#[derive(Enum, PartialEq)]
+pub enum Division {
+  #[name = "1/16"]
+  Sixteenth,
+  #[name = "1/8"]
+  Eighth,
+  #[name = "1/4"]
+  Quarter,
+  #[name = "1/2"]
+  Half,
+  #[name = "1 Bar"]
+  Whole,
+  #[name = "2 Bars"]
+  TwoBars,
+}
+
+#[derive(Enum, PartialEq)]
+pub enum Slope {
+  #[name = "12 dB/oct"]
+  TwelveDb,
+  #[name = "24 dB/oct"]
+  TwentyFourDb,
+}
+
 #[derive(Params)]
 pub struct ReverbParameters {
   #[persist = "editor-state"]
   pub editor_state: Arc<ViziaState>,
 
+  // The unscaled editor size that `editor_state` reports, which the size handle in the editor changes
+  #[persist = "editor-size"]
+  pub editor_size: Arc<RwLock<(u32, u32)>>,
+
   #[id = "size"]
   pub size: FloatParam,
 
   #[id = "predelay"]
   pub predelay: FloatParam,
 
+  // Keeps the id of the former toggle, so host automation still applies. Sessions that stored it as a boolean
+  // are converted in `Plugin::filter_state`.
   #[id = "reverse"]
-  pub reverse: BoolParam,
+  pub reverse: FloatParam,
 
   #[id = "speed"]
   pub speed: FloatParam,
@@ -43,12 +127,206 @@ pub struct ReverbParameters {
 
   #[id = "mix"]
   pub mix: FloatParam,
+
+  #[id = "quality"]
+  pub quality: EnumParam<Quality>,
+
+  #[id = "early"]
+  pub early: EnumParam<EarlyReflections>,
+
+  #[id = "early_level"]
+  pub early_level: FloatParam,
+
+  #[id = "room_width"]
+  pub room_width: FloatParam,
+
+  #[id = "room_depth"]
+  pub room_depth: FloatParam,
+
+  #[id = "room_height"]
+  pub room_height: FloatParam,
+
+  #[id = "source_x"]
+  pub source_x: FloatParam,
+
+  #[id = "source_y"]
+  pub source_y: FloatParam,
+
+  #[id = "source_z"]
+  pub source_z: FloatParam,
+
+  #[id = "listener_x"]
+  pub listener_x: FloatParam,
+
+  #[id = "listener_y"]
+  pub listener_y: FloatParam,
+
+  #[id = "listener_z"]
+  pub listener_z: FloatParam,
+
+  #[id = "wall_absorption"]
+  pub wall_absorption: FloatParam,
+
+  #[id = "low_decay"]
+  pub low_decay: FloatParam,
+
+  #[id = "mid_decay"]
+  pub mid_decay: FloatParam,
+
+  #[id = "high_decay"]
+  pub high_decay: FloatParam,
+
+  #[id = "low_crossover"]
+  pub low_crossover: FloatParam,
+
+  #[id = "high_crossover"]
+  pub high_crossover: FloatParam,
+
+  #[id = "low_cut"]
+  pub low_cut: FloatParam,
+
+  #[id = "low_cut_resonance"]
+  pub low_cut_resonance: FloatParam,
+
+  #[id = "low_cut_slope"]
+  pub low_cut_slope: EnumParam<Slope>,
+
+  #[id = "high_cut"]
+  pub high_cut: FloatParam,
+
+  #[id = "high_cut_resonance"]
+  pub high_cut_resonance: FloatParam,
+
+  #[id = "high_cut_slope"]
+  pub high_cut_slope: EnumParam<Slope>,
+
+  #[id = "eq"]
+  pub eq: BoolParam,
+
+  #[id = "low_shelf_freq"]
+  pub low_shelf_freq: FloatParam,
+
+  #[id = "low_shelf_gain"]
+  pub low_shelf_gain: FloatParam,
+
+  #[id = "peak_freq"]
+  pub peak_freq: FloatParam,
+
+  #[id = "peak_gain"]
+  pub peak_gain: FloatParam,
+
+  #[id = "peak_q"]
+  pub peak_q: FloatParam,
+
+  #[id = "high_shelf_freq"]
+  pub high_shelf_freq: FloatParam,
+
+  #[id = "high_shelf_gain"]
+  pub high_shelf_gain: FloatParam,
+
+  #[id = "shimmer_pitch"]
+  pub shimmer_pitch: FloatParam,
+
+  #[id = "shimmer_quantize"]
+  pub shimmer_quantize: BoolParam,
+
+  #[id = "shimmer_2_pitch"]
+  pub shimmer_2_pitch: FloatParam,
+
+  #[id = "shimmer_2_level"]
+  pub shimmer_2_level: FloatParam,
+
+  #[id = "shimmer_spread"]
+  pub shimmer_spread: FloatParam,
+
+  #[id = "shimmer_mode"]
+  pub shimmer_mode: EnumParam<ShimmerMode>,
+
+  #[id = "reverse_tail"]
+  pub reverse_tail: BoolParam,
+
+  #[id = "reverse_tail_length"]
+  pub reverse_tail_length: FloatParam,
+
+  #[id = "reverse_tail_sync"]
+  pub reverse_tail_sync: BoolParam,
+
+  #[id = "reverse_tail_division"]
+  pub reverse_tail_division: EnumParam<Division>,
+
+  #[id = "reverse_crossfade"]
+  pub reverse_crossfade: FloatParam,
+
+  #[id = "width"]
+  pub width: FloatParam,
+
+  #[id = "mono_bass"]
+  pub mono_bass: BoolParam,
+
+  #[id = "mono_bass_freq"]
+  pub mono_bass_freq: FloatParam,
+
+  #[id = "injection_distribution"]
+  pub injection_distribution: FloatParam,
+
+  #[id = "injection_rotation"]
+  pub injection_rotation: FloatParam,
+
+  #[id = "injection_spread"]
+  pub injection_spread: FloatParam,
+
+  #[id = "lfo_shape"]
+  pub lfo_shape: EnumParam<LfoShape>,
+
+  #[id = "lfo_phase_spread"]
+  pub lfo_phase_spread: FloatParam,
+
+  #[id = "lfo_rate_spread"]
+  pub lfo_rate_spread: FloatParam,
+
+  #[id = "lfo_seed"]
+  pub lfo_seed: IntParam,
+
+  #[id = "grain_size"]
+  pub grain_size: FloatParam,
+
+  #[id = "grain_density"]
+  pub grain_density: IntParam,
+
+  #[id = "grain_window"]
+  pub grain_window: EnumParam<GrainWindow>,
+
+  #[id = "grain_curve"]
+  pub grain_curve: FloatParam,
+
+  #[id = "grain_pitch_jitter"]
+  pub grain_pitch_jitter: FloatParam,
+
+  #[id = "grain_reverse"]
+  pub grain_reverse: FloatParam,
+
+  #[id = "mix_mode"]
+  pub mix_mode: EnumParam<MixMode>,
+
+  #[id = "dry_level"]
+  pub dry_level: FloatParam,
+
+  #[id = "wet_level"]
+  pub wet_level: FloatParam,
+
+  // Non-automatable and left out of the editor presets and the restored state, so it stays locked while presets
+  // and sessions are loaded.
+  #[id = "kill_dry"]
+  pub kill_dry: BoolParam,
 }
 
 impl Default for ReverbParameters {
   fn default() -> Self {
+    let editor_size = Arc::new(RwLock::new(editor::DEFAULT_SIZE));
+
     Self {
-      editor_state: editor::default_state(),
+      editor_state: editor::default_state(editor_size.clone()),
+      editor_size,
 
       size: FloatParam::new(
         "Size",
@@ -73,7 +351,10 @@ impl Default for ReverbParameters {
       .with_unit(" ms")
       .with_value_to_string(v2s_f32_digits(2)),
 
-      reverse: BoolParam::new("Reverse", false),
+      reverse: FloatParam::new("Reverse", 0., FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
 
       speed: FloatParam::new(
         "Speed",
@@ -117,6 +398,626 @@ impl Default for ReverbParameters {
         .with_unit(" %")
         .with_value_to_string(v2s_f32_percentage(2))
         .with_string_to_value(s2v_f32_percentage()),
+
+      quality: EnumParam::new("Quality", Quality::Standard),
+
+      early: EnumParam::new("Early", EarlyReflections::Room),
+
+      early_level: FloatParam::new("Early Level", 1., FloatRange::Linear { min: 0., max: 2. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      room_width: FloatParam::new(
+        "Room Width",
+        10.0,
+        FloatRange::Skewed {
+          min: MIN_ROOM_DIMENSION,
+          max: MAX_ROOM_DIMENSION,
+          factor: 0.5,
+        },
+      )
+      .with_unit(" m")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      room_depth: FloatParam::new(
+        "Room Depth",
+        14.0,
+        FloatRange::Skewed {
+          min: MIN_ROOM_DIMENSION,
+          max: MAX_ROOM_DIMENSION,
+          factor: 0.5,
+        },
+      )
+      .with_unit(" m")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      room_height: FloatParam::new(
+        "Room Height",
+        4.0,
+        FloatRange::Skewed {
+          min: MIN_ROOM_DIMENSION,
+          max: MAX_ROOM_DIMENSION,
+          factor: 0.5,
+        },
+      )
+      .with_unit(" m")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      source_x: FloatParam::new("Source X", 0.5, FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      source_y: FloatParam::new("Source Y", 0.3, FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      source_z: FloatParam::new("Source Z", 0.4, FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      listener_x: FloatParam::new("Listener X", 0.5, FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      listener_y: FloatParam::new("Listener Y", 0.7, FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      listener_z: FloatParam::new("Listener Z", 0.4, FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      wall_absorption: FloatParam::new(
+        "Wall Absorption",
+        0.3,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      low_decay: FloatParam::new(
+        "Low Decay",
+        1.,
+        FloatRange::SymmetricalSkewed {
+          min: 0.25,
+          max: 4.,
+          factor: 1.,
+          center: 1.,
+        },
+      )
+      .with_unit(" x")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      mid_decay: FloatParam::new(
+        "Mid Decay",
+        1.,
+        FloatRange::SymmetricalSkewed {
+          min: 0.25,
+          max: 4.,
+          factor: 1.,
+          center: 1.,
+        },
+      )
+      .with_unit(" x")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      high_decay: FloatParam::new(
+        "High Decay",
+        1.,
+        FloatRange::SymmetricalSkewed {
+          min: 0.25,
+          max: 4.,
+          factor: 1.,
+          center: 1.,
+        },
+      )
+      .with_unit(" x")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      low_crossover: FloatParam::new(
+        "Low Crossover",
+        250.,
+        FloatRange::Skewed {
+          min: 50.,
+          max: 1000.,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" Hz")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      high_crossover: FloatParam::new(
+        "High Crossover",
+        4000.,
+        FloatRange::Skewed {
+          min: 1000.,
+          max: 16000.,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" Hz")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      low_cut: FloatParam::new(
+        "Low Cut",
+        20.,
+        FloatRange::Skewed {
+          min: 20.,
+          max: 1000.,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" Hz")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      low_cut_resonance: FloatParam::new(
+        "Low Cut Resonance",
+        0.707,
+        FloatRange::Skewed {
+          min: 0.5,
+          max: 10.,
+          factor: 0.5,
+        },
+      )
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      low_cut_slope: EnumParam::new("Low Cut Slope", Slope::TwelveDb),
+
+      high_cut: FloatParam::new(
+        "High Cut",
+        20000.,
+        FloatRange::Skewed {
+          min: 1000.,
+          max: 20000.,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" Hz")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      high_cut_resonance: FloatParam::new(
+        "High Cut Resonance",
+        0.707,
+        FloatRange::Skewed {
+          min: 0.5,
+          max: 10.,
+          factor: 0.5,
+        },
+      )
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      high_cut_slope: EnumParam::new("High Cut Slope", Slope::TwelveDb),
+
+      eq: BoolParam::new("EQ", false),
+
+      low_shelf_freq: FloatParam::new(
+        "Low Shelf Freq",
+        200.,
+        FloatRange::Skewed {
+          min: 20.,
+          max: 1000.,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" Hz")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      low_shelf_gain: FloatParam::new(
+        "Low Shelf Gain",
+        0.,
+        FloatRange::Linear {
+          min: -18.,
+          max: 18.,
+        },
+      )
+      .with_unit(" dB")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      peak_freq: FloatParam::new(
+        "Peak Freq",
+        1000.,
+        FloatRange::Skewed {
+          min: 20.,
+          max: 20000.,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" Hz")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      peak_gain: FloatParam::new(
+        "Peak Gain",
+        0.,
+        FloatRange::Linear {
+          min: -18.,
+          max: 18.,
+        },
+      )
+      .with_unit(" dB")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      peak_q: FloatParam::new(
+        "Peak Q",
+        0.707,
+        FloatRange::Skewed {
+          min: 0.1,
+          max: 10.,
+          factor: 0.5,
+        },
+      )
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      high_shelf_freq: FloatParam::new(
+        "High Shelf Freq",
+        6000.,
+        FloatRange::Skewed {
+          min: 1000.,
+          max: 20000.,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" Hz")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      high_shelf_gain: FloatParam::new(
+        "High Shelf Gain",
+        0.,
+        FloatRange::Linear {
+          min: -18.,
+          max: 18.,
+        },
+      )
+      .with_unit(" dB")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      shimmer_pitch: FloatParam::new(
+        "Shimmer Pitch",
+        12.,
+        FloatRange::Linear {
+          min: -24.,
+          max: 24.,
+        },
+      )
+      .with_unit(" st")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      shimmer_quantize: BoolParam::new("Shimmer Quantize", true),
+
+      shimmer_2_pitch: FloatParam::new(
+        "Shimmer 2 Pitch",
+        19.,
+        FloatRange::Linear {
+          min: -24.,
+          max: 24.,
+        },
+      )
+      .with_unit(" st")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      shimmer_2_level: FloatParam::new(
+        "Shimmer 2 Level",
+        0.,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      shimmer_spread: FloatParam::new(
+        "Shimmer Spread",
+        0.,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      shimmer_mode: EnumParam::new("Shimmer Mode", ShimmerMode::Classic),
+
+      reverse_tail: BoolParam::new("Reverse Tail", false),
+
+      reverse_tail_length: FloatParam::new(
+        "Reverse Tail Length",
+        500.,
+        FloatRange::Skewed {
+          min: MIN_REVERSE_TAIL_LENGTH,
+          max: MAX_REVERSE_TAIL_LENGTH,
+          factor: 0.5,
+        },
+      )
+      .with_unit(" ms")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      reverse_tail_sync: BoolParam::new("Reverse Tail Sync", false),
+
+      reverse_tail_division: EnumParam::new("Reverse Tail Division", Division::Quarter),
+
+      reverse_crossfade: FloatParam::new(
+        "Reverse Crossfade",
+        MIN_PREDELAY,
+        FloatRange::Skewed {
+          min: 1.,
+          max: MAX_PREDELAY,
+          factor: 0.333333,
+        },
+      )
+      .with_unit(" ms")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      width: FloatParam::new("Width", 1., FloatRange::Linear { min: 0., max: 2. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      mono_bass: BoolParam::new("Mono Bass", false),
+
+      mono_bass_freq: FloatParam::new(
+        "Mono Bass Freq",
+        120.,
+        FloatRange::Skewed {
+          min: 20.,
+          max: 500.,
+          factor: 0.5,
+        },
+      )
+      .with_unit(" Hz")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      injection_distribution: FloatParam::new(
+        "Injection Distribution",
+        0.,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      injection_rotation: FloatParam::new(
+        "Injection Rotation",
+        0.,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      injection_spread: FloatParam::new(
+        "Injection Spread",
+        1.,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      lfo_shape: EnumParam::new("LFO Shape", LfoShape::Sine),
+
+      lfo_phase_spread: FloatParam::new(
+        "LFO Phase Spread",
+        1.,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      lfo_rate_spread: FloatParam::new(
+        "LFO Rate Spread",
+        0.,
+        FloatRange::Linear { min: 0., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      lfo_seed: IntParam::new("LFO Seed", 0, IntRange::Linear { min: 0, max: 999 }),
+
+      grain_size: FloatParam::new(
+        "Grain Size",
+        500.,
+        FloatRange::Skewed {
+          min: MIN_GRAIN_SIZE,
+          max: MAX_GRAIN_SIZE,
+          factor: 0.5,
+        },
+      )
+      .with_unit(" ms")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      grain_density: IntParam::new(
+        "Grain Density",
+        MIN_GRAIN_DENSITY as i32,
+        IntRange::Linear {
+          min: MIN_GRAIN_DENSITY as i32,
+          max: MAX_GRAIN_DENSITY as i32,
+        },
+      ),
+
+      grain_window: EnumParam::new("Grain Window", GrainWindow::Hann),
+
+      grain_curve: FloatParam::new(
+        "Grain Position Curve",
+        0.,
+        FloatRange::Linear { min: -1., max: 1. },
+      )
+      .with_unit(" %")
+      .with_value_to_string(v2s_f32_percentage(2))
+      .with_string_to_value(s2v_f32_percentage()),
+
+      grain_pitch_jitter: FloatParam::new(
+        "Grain Pitch Jitter",
+        0.,
+        FloatRange::Linear {
+          min: 0.,
+          max: MAX_GRAIN_PITCH_JITTER,
+        },
+      )
+      .with_unit(" ct")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      grain_reverse: FloatParam::new("Grain Reverse", 0., FloatRange::Linear { min: 0., max: 1. })
+        .with_unit(" %")
+        .with_value_to_string(v2s_f32_percentage(2))
+        .with_string_to_value(s2v_f32_percentage()),
+
+      mix_mode: EnumParam::new("Mix Mode", MixMode::EqualPower),
+
+      dry_level: FloatParam::new(
+        "Dry Level",
+        0.,
+        FloatRange::Linear {
+          min: -60.,
+          max: 12.,
+        },
+      )
+      .with_unit(" dB")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      wet_level: FloatParam::new(
+        "Wet Level",
+        0.,
+        FloatRange::Linear {
+          min: -60.,
+          max: 12.,
+        },
+      )
+      .with_unit(" dB")
+      .with_value_to_string(v2s_f32_digits(2)),
+
+      kill_dry: BoolParam::new("Kill Dry", false).non_automatable(),
+    }
+  }
+}
+
+impl ReverbParameters {
+  pub fn get_room_geometry(&self) -> RoomGeometry {
+    RoomGeometry {
+      dimensions: (
+        self.room_width.value(),
+        self.room_depth.value(),
+        self.room_height.value(),
+      ),
+      source: (
+        self.source_x.value(),
+        self.source_y.value(),
+        self.source_z.value(),
+      ),
+      listener: (
+        self.listener_x.value(),
+        self.listener_y.value(),
+        self.listener_z.value(),
+      ),
+      absorption: self.wall_absorption.value(),
     }
   }
+
+  pub fn set_process_params(&self, process_params: &mut ProcessParams, tempo: f32) {
+    process_params.set(&Settings {
+      reverse: self.reverse.value(),
+      predelay: self.predelay.value(),
+      size: self.size.value(),
+      speed: self.speed.value(),
+      depth: self.depth.value(),
+      absorb: self.absorb.value(),
+      decay: self.decay.value(),
+      tilt: self.tilt.value(),
+      shimmer: self.shimmer.value(),
+      mix: self.mix.value(),
+      quality: self.quality.value() as i32,
+      early: self.early.value() as i32,
+      early_level: self.early_level.value(),
+      low_decay: self.low_decay.value(),
+      mid_decay: self.mid_decay.value(),
+      high_decay: self.high_decay.value(),
+      low_crossover: self.low_crossover.value(),
+      high_crossover: self.high_crossover.value(),
+      low_cut: self.low_cut.value(),
+      low_cut_resonance: self.low_cut_resonance.value(),
+      low_cut_slope: self.low_cut_slope.value() as i32,
+      high_cut: self.high_cut.value(),
+      high_cut_resonance: self.high_cut_resonance.value(),
+      high_cut_slope: self.high_cut_slope.value() as i32,
+      eq: self.eq.value(),
+      low_shelf_freq: self.low_shelf_freq.value(),
+      low_shelf_gain: self.low_shelf_gain.value(),
+      peak_freq: self.peak_freq.value(),
+      peak_gain: self.peak_gain.value(),
+      peak_q: self.peak_q.value(),
+      high_shelf_freq: self.high_shelf_freq.value(),
+      high_shelf_gain: self.high_shelf_gain.value(),
+      shimmer_pitch: self.shimmer_pitch.value(),
+      shimmer_quantize: self.shimmer_quantize.value(),
+      shimmer_2_pitch: self.shimmer_2_pitch.value(),
+      shimmer_2_level: self.shimmer_2_level.value(),
+      shimmer_spread: self.shimmer_spread.value(),
+      shimmer_mode: self.shimmer_mode.value() as i32,
+      reverse_tail: self.reverse_tail.value(),
+      reverse_tail_length: self.reverse_tail_length.value(),
+      reverse_tail_sync: self.reverse_tail_sync.value(),
+      reverse_tail_division: self.reverse_tail_division.value() as i32,
+      tempo,
+      reverse_crossfade: self.reverse_crossfade.value(),
+      width: self.width.value(),
+      mono_bass: self.mono_bass.value(),
+      mono_bass_freq: self.mono_bass_freq.value(),
+      injection_distribution: self.injection_distribution.value(),
+      injection_rotation: self.injection_rotation.value(),
+      injection_spread: self.injection_spread.value(),
+      lfo_shape: self.lfo_shape.value() as i32,
+      lfo_phase_spread: self.lfo_phase_spread.value(),
+      lfo_rate_spread: self.lfo_rate_spread.value(),
+      lfo_seed: self.lfo_seed.value(),
+      grain_size: self.grain_size.value(),
+      grain_density: self.grain_density.value(),
+      grain_window: self.grain_window.value() as i32,
+      grain_curve: self.grain_curve.value(),
+      grain_pitch_jitter: self.grain_pitch_jitter.value(),
+      grain_reverse: self.grain_reverse.value(),
+      mix_mode: self.mix_mode.value() as i32,
+      dry_level: self.dry_level.value(),
+      wet_level: self.wet_level.value(),
+      kill_dry: self.kill_dry.value(),
+    });
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::ReverbParameters;
+  use nih_plug::prelude::Params;
+  use nih_plug_vizia::ViziaState;
+  use std::sync::{Arc, RwLock};
+
+  #[test]
+  fn should_restore_the_editor_scale_and_size() {
+    let editor_size = Arc::new(RwLock::new((640, 600)));
+    let params = ReverbParameters {
+      editor_state: ViziaState::new_with_default_scale_factor(
+        {
+          let editor_size = editor_size.clone();
+          move || *editor_size.read().unwrap()
+        },
+        1.5,
+      ),
+      editor_size,
+      ..Default::default()
+    };
+
+    let restored_params = ReverbParameters::default();
+    restored_params.deserialize_fields(&params.serialize_fields());
+    assert_eq!(restored_params.editor_state.user_scale_factor(), 1.5);
+    assert_eq!(*restored_params.editor_size.read().unwrap(), (640, 600));
+    assert_eq!(
+      restored_params.editor_state.scaled_logical_size(),
+      (960, 900)
+    );
+  }
 }
@@ -1,5 +1,8 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use reverb::{shared::delay_line::DelayLine, EarlyReflections};
+use reverb::{
+  shared::{delay_line::DelayLine, quality::Quality},
+  EarlyReflections, ReflectionPattern,
+};
 
 fn generate_signal() -> f32 {
   fastrand::f32() * 2. - 1.
@@ -11,7 +14,7 @@ fn generate_signal_stream(length: usize) -> Vec<f32> {
 
 fn early_rf_bench(c: &mut Criterion) {
   let sample_rate = 44100.;
-  let mut early_rf = EarlyReflections::new();
+  let mut early_rf = EarlyReflections::new(sample_rate);
   let mut delay_lines = [
     DelayLine::new((sample_rate * 1.) as usize, sample_rate),
     DelayLine::new((sample_rate * 1.) as usize, sample_rate),
@@ -26,7 +29,13 @@ fn early_rf_bench(c: &mut Criterion) {
         delay_lines.iter_mut().for_each(|delay_line| {
           delay_line.write(*signal);
         });
-        early_rf.process(40., &mut delay_lines);
+        early_rf.process(
+          40.,
+          &mut delay_lines,
+          ReflectionPattern::Room,
+          1.,
+          Quality::Standard,
+        );
       }
     })
   });
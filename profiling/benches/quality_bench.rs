@@ -0,0 +1,44 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use reverb::{Params, Reverb, Settings};
+
+fn generate_signal() -> f32 {
+  fastrand::f32() * 2. - 1.
+}
+
+fn generate_stereo_signal_stream(length: usize) -> Vec<(f32, f32)> {
+  (0..length)
+    .map(|_| (generate_signal(), generate_signal()))
+    .collect()
+}
+
+fn quality_bench(c: &mut Criterion) {
+  let signal_stream = generate_stereo_signal_stream(44100);
+
+  [("eco", 0), ("standard", 1), ("high", 2)]
+    .into_iter()
+    .for_each(|(name, quality)| {
+      let mut reverb = Reverb::new(44100.);
+      let mut params = Params::new(44100.);
+      params.set(&Settings {
+        speed: 3.,
+        depth: -0.2,
+        absorb: 0.8,
+        decay: 0.8,
+        tilt: 0.1,
+        shimmer: 0.5,
+        quality,
+        ..Default::default()
+      });
+
+      c.bench_function(&format!("reverb_quality_{name}"), |b| {
+        b.iter(|| {
+          for signal in &signal_stream {
+            reverb.process(*signal, &mut params);
+          }
+        })
+      });
+    });
+}
+
+criterion_group!(benches, quality_bench);
+criterion_main!(benches);
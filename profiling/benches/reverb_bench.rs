@@ -1,5 +1,5 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use reverb::{Params, Reverb};
+use reverb::{Params, Reverb, Settings};
 
 fn generate_signal() -> f32 {
   fastrand::f32() * 2. - 1.
@@ -14,7 +14,15 @@ fn generate_stereo_signal_stream(length: usize) -> Vec<(f32, f32)> {
 fn reverb_bench(c: &mut Criterion) {
   let mut reverb = Reverb::new(44100.);
   let mut params = Params::new(44100.);
-  params.set(0., 7., 80., 3., -0.2, 0.8, 0.8, 0.1, 0.5, 0.5);
+  params.set(&Settings {
+    speed: 3.,
+    depth: -0.2,
+    absorb: 0.8,
+    decay: 0.8,
+    tilt: 0.1,
+    shimmer: 0.5,
+    ..Default::default()
+  });
   let signal_stream = generate_stereo_signal_stream(44100);
 
   c.bench_function("reverb", |b| {
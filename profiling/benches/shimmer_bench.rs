@@ -0,0 +1,56 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use reverb::{
+  shared::quality::Quality, GrainWindow, LfoShape, ReflectionPattern, ShimmerMode, Taps, TapsParams,
+};
+
+fn generate_signal() -> f32 {
+  fastrand::f32() * 2. - 1.
+}
+
+fn generate_stereo_signal_stream(length: usize) -> Vec<(f32, f32)> {
+  (0..length)
+    .map(|_| (generate_signal(), generate_signal()))
+    .collect()
+}
+
+fn shimmer_bench(c: &mut Criterion) {
+  let signal_stream = generate_stereo_signal_stream(44100);
+
+  for (name, shimmer_mode) in [
+    ("shimmer_classic", ShimmerMode::Classic),
+    ("shimmer_high_quality", ShimmerMode::HighQuality),
+  ] {
+    let mut taps = Taps::new(44100.);
+    c.bench_function(name, |b| {
+      b.iter(|| {
+        for signal in &signal_stream {
+          taps.process(
+            *signal,
+            &TapsParams {
+              size: 80.,
+              speed: 2.,
+              depth: -0.1,
+              lfo: (LfoShape::Sine, 1., 0., 0),
+              grains: (500., 2, GrainWindow::Hann, 0., 0., 0.),
+              diffuse: 0.5,
+              absorb: 0.5,
+              decay: 0.8,
+              decay_multipliers: (1., 1., 1.),
+              crossovers: (250., 4000.),
+              shimmer: 0.5,
+              shimmer_voices: (12., 19., 1., 1.),
+              shimmer_mode,
+              injection: (0., 0., 1.),
+              quality: Quality::Standard,
+              early: ReflectionPattern::Room,
+              early_level: 1.,
+            },
+          );
+        }
+      })
+    });
+  }
+}
+
+criterion_group!(benches, shimmer_bench);
+criterion_main!(benches);
@@ -1,5 +1,7 @@
 use criterion::{criterion_group, criterion_main, Criterion};
-use reverb::Taps;
+use reverb::{
+  shared::quality::Quality, GrainWindow, LfoShape, ReflectionPattern, ShimmerMode, Taps, TapsParams,
+};
 
 fn generate_signal() -> f32 {
   fastrand::f32() * 2. - 1.
@@ -17,7 +19,28 @@ fn taps_bench(c: &mut Criterion) {
   c.bench_function("taps", |b| {
     b.iter(|| {
       for signal in &signal_stream {
-        taps.process(*signal, 80., 2., -0.1, 0.5, 0.5, 0.8, 0.5);
+        taps.process(
+          *signal,
+          &TapsParams {
+            size: 80.,
+            speed: 2.,
+            depth: -0.1,
+            lfo: (LfoShape::Sine, 1., 0., 0),
+            grains: (500., 2, GrainWindow::Hann, 0., 0., 0.),
+            diffuse: 0.5,
+            absorb: 0.5,
+            decay: 0.8,
+            decay_multipliers: (1., 1., 1.),
+            crossovers: (250., 4000.),
+            shimmer: 0.5,
+            shimmer_voices: (12., 19., 0., 0.),
+            shimmer_mode: ShimmerMode::Classic,
+            injection: (0., 0., 1.),
+            quality: Quality::Standard,
+            early: ReflectionPattern::Room,
+            early_level: 1.,
+          },
+        );
       }
     })
   });
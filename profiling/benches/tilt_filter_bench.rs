@@ -0,0 +1,51 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use reverb::{
+  shared::{bilinear_transform::BilinearTransform, biquad_filter::BiquadFilter},
+  TiltFilter,
+};
+
+fn generate_signal() -> f32 {
+  fastrand::f32() * 2. - 1.
+}
+
+fn generate_stereo_signal_stream(length: usize) -> Vec<(f32, f32)> {
+  (0..length)
+    .map(|_| (generate_signal(), generate_signal()))
+    .collect()
+}
+
+fn tilt_filter_bench(c: &mut Criterion) {
+  let mut tilt_filter = TiltFilter::new(44100.);
+  let signal_stream = generate_stereo_signal_stream(44100);
+  let tilt_stream: Vec<f32> = (0..44100).map(|i| i as f32 / 44100.).collect();
+
+  c.bench_function("tilt_filter_static", |b| {
+    b.iter(|| {
+      for signal in &signal_stream {
+        black_box(tilt_filter.process(*signal, black_box(0.7)));
+      }
+    })
+  });
+  // Computes the coefficients on every sample, like the tilt filter did before it had a table
+  let bilinear_transform = BilinearTransform::new(44100.);
+  let mut biquad_filter = BiquadFilter::new();
+  c.bench_function("tilt_filter_moving_per_sample", |b| {
+    b.iter(|| {
+      for (signal, tilt) in signal_stream.iter().zip(&tilt_stream) {
+        let coefficients =
+          bilinear_transform.process(TiltFilter::get_s_domain_coefficients(black_box(*tilt)));
+        black_box(biquad_filter.process(*signal, coefficients));
+      }
+    })
+  });
+  c.bench_function("tilt_filter_moving", |b| {
+    b.iter(|| {
+      for (signal, tilt) in signal_stream.iter().zip(&tilt_stream) {
+        black_box(tilt_filter.process(*signal, black_box(*tilt)));
+      }
+    })
+  });
+}
+
+criterion_group!(benches, tilt_filter_bench);
+criterion_main!(benches);
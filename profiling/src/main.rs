@@ -1,4 +1,4 @@
-use reverb::{Params, Reverb};
+use reverb::{Params, Reverb, Settings};
 
 fn generate_signal() -> f32 {
   fastrand::f32() * 2. - 1.
@@ -7,7 +7,15 @@ fn generate_signal() -> f32 {
 fn main() {
   let mut reverb = Reverb::new(44100.);
   let mut params = Params::new(44100.);
-  params.set(0., 7., 80., 3., -0.2, 0.8, 0.8, 0.1, 0.5, 0.5);
+  params.set(&Settings {
+    speed: 3.,
+    depth: -0.2,
+    absorb: 0.8,
+    decay: 0.8,
+    tilt: 0.1,
+    shimmer: 0.5,
+    ..Default::default()
+  });
 
   loop {
     let input = (generate_signal(), generate_signal());
@@ -0,0 +1,80 @@
+use crate::{
+  output_eq::{BandType, EqBands},
+  params::Params,
+  tilt_filter::TiltFilter,
+};
+use std::f32::consts::{FRAC_1_SQRT_2, TAU};
+
+/// Returns the gain in decibels the tilt filter and the output eq apply to the wet signal at `freq`. Takes
+/// `tilt` and `eq` with the same ranges as `Params::set` and evaluates the analog prototypes, so the response
+/// doesn't depend on the sample rate.
+pub fn get_frequency_response(freq: f32, tilt: f32, eq: Option<EqBands>) -> f32 {
+  let w = TAU * freq;
+  let tilt_magnitude = get_magnitude(
+    TiltFilter::get_s_domain_coefficients(Params::map_tilt(tilt)),
+    w,
+  );
+  let eq_magnitude = match eq {
+    Some((
+      (low_shelf_freq, low_shelf_gain),
+      (peak_freq, peak_gain, peak_q),
+      (high_shelf_freq, high_shelf_gain),
+    )) => [
+      (
+        BandType::LowShelf,
+        low_shelf_freq,
+        low_shelf_gain,
+        FRAC_1_SQRT_2,
+      ),
+      (BandType::Peak, peak_freq, peak_gain, peak_q),
+      (
+        BandType::HighShelf,
+        high_shelf_freq,
+        high_shelf_gain,
+        FRAC_1_SQRT_2,
+      ),
+    ]
+    .into_iter()
+    .map(|(band_type, band_freq, gain, q)| {
+      get_magnitude(
+        band_type.get_s_domain_coefficients(TAU * band_freq, gain, q),
+        w,
+      )
+    })
+    .product(),
+    None => 1.,
+  };
+
+  (tilt_magnitude * eq_magnitude).log10() * 20.
+}
+
+/// Evaluates `|H(jw)|` for coefficients ordered from the `s^2` term down to the constant term.
+fn get_magnitude((b, a): ([f32; 3], [f32; 3]), w: f32) -> f32 {
+  let w_squared = w * w;
+  let numerator = (b[2] - b[0] * w_squared).hypot(b[1] * w);
+  let denominator = (a[2] - a[0] * w_squared).hypot(a[1] * w);
+  numerator / denominator
+}
+
+#[cfg(test)]
+mod tests {
+  use super::get_frequency_response;
+
+  #[test]
+  fn should_follow_tilt_and_eq() {
+    let flat = [50., 1000., 15000.].map(|freq| get_frequency_response(freq, 0., None));
+    let bright = [50., 1000., 15000.].map(|freq| get_frequency_response(freq, 1., None));
+    let dark = [50., 1000., 15000.].map(|freq| get_frequency_response(freq, -1., None));
+    assert!(bright[2] - bright[0] > flat[2] - flat[0] + 6.);
+    assert!(dark[2] - dark[0] < flat[2] - flat[0] - 6.);
+
+    let eq = Some(((100., 0.), (1000., 6., 2.), (10000., 0.)));
+    let peak = get_frequency_response(1000., 0., eq) - flat[1];
+    assert!((peak - 6.).abs() < 0.01);
+    let eq = Some(((100., -12.), (1000., 0., 1.), (10000., 9.)));
+    assert!(
+      (get_frequency_response(20., 0., eq) - get_frequency_response(20., 0., None) + 12.).abs()
+        < 0.1
+    );
+  }
+}
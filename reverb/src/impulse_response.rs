@@ -0,0 +1,187 @@
+use crate::{
+  shared::{bilinear_transform::BilinearTransform, biquad_filter::BiquadFilter},
+  Params, Reverb, RoomReflections,
+};
+use std::f32::consts::SQRT_2;
+
+/// Center frequencies of the octave bands the spectrogram is split into.
+pub const SPECTROGRAM_BANDS: [f32; 8] = [125., 250., 500., 1000., 2000., 4000., 8000., 16000.];
+// The energy decay and spectrogram don't go below this level
+const FLOOR: f32 = -90.;
+
+/// The wet signal of a reverb that is excited by a single impulse. It's rendered offline, so the decay of the
+/// current settings can be shown before any audio is played.
+pub struct ImpulseResponse {
+  sample_rate: f32,
+  samples: Vec<(f32, f32)>,
+}
+
+impl ImpulseResponse {
+  /// Renders `length` seconds of the wet signal of a new reverb instance. The first call to `Params::set` resets
+  /// the smoothed params, so `params` should be set once before rendering.
+  pub fn render(
+    sample_rate: f32,
+    length: f32,
+    room_reflections: RoomReflections,
+    params: &mut Params,
+  ) -> Self {
+    let mut reverb = Reverb::new(sample_rate);
+    reverb.set_room_reflections(room_reflections);
+    let samples = (0..(sample_rate * length) as usize)
+      .map(|i| {
+        let input = if i == 0 { 1. } else { 0. };
+        reverb.process((input, input), params);
+        reverb.get_wet()
+      })
+      .collect();
+
+    Self {
+      sample_rate,
+      samples,
+    }
+  }
+
+  /// Returns the backward integrated energy in decibels at `points` evenly spaced moments, relative to the total
+  /// energy of the response.
+  pub fn get_energy_decay(&self, points: usize) -> Vec<f32> {
+    let energy_decay = self.get_schroeder_curve();
+    (0..points)
+      .map(|i| energy_decay[i * energy_decay.len() / points])
+      .collect()
+  }
+
+  /// Estimates the RT60 in seconds from the time it takes the energy decay to fall from -5 dB to -35 dB. Returns
+  /// `None` when the response is still ringing at the end of the render, as the estimate would be too short.
+  pub fn get_rt60(&self) -> Option<f32> {
+    let energy_decay = self.get_schroeder_curve();
+    if energy_decay[energy_decay.len() * 9 / 10] > -40. {
+      return None;
+    }
+    let start = energy_decay.iter().position(|x| *x <= -5.)?;
+    let end = energy_decay.iter().position(|x| *x <= -35.)?;
+    Some((end - start) as f32 / self.sample_rate * 2.)
+  }
+
+  /// Returns the level in decibels of every octave band in `SPECTROGRAM_BANDS` for `frames` evenly spaced parts of
+  /// the response, relative to the loudest band of all frames.
+  pub fn get_spectrogram(&self, frames: usize) -> Vec<[f32; SPECTROGRAM_BANDS.len()]> {
+    let bilinear_transform = BilinearTransform::new(self.sample_rate);
+    let frame_length = (self.samples.len() / frames).max(1);
+    let mut spectrogram = vec![[0.; SPECTROGRAM_BANDS.len()]; frames];
+
+    for (band, freq) in SPECTROGRAM_BANDS.into_iter().enumerate() {
+      let w = bilinear_transform.prewarp(freq);
+      let coefficients =
+        bilinear_transform.process(([0., w / SQRT_2, 0.], [1., w / SQRT_2, w * w]));
+      let mut biquad_filter = BiquadFilter::new();
+
+      for (i, input) in self.samples.iter().enumerate().take(frame_length * frames) {
+        let (left, right) = biquad_filter.process(*input, coefficients);
+        spectrogram[i / frame_length][band] += left * left + right * right;
+      }
+    }
+
+    let max = spectrogram
+      .iter()
+      .flatten()
+      .fold(f32::MIN_POSITIVE, |max, energy| max.max(*energy));
+    spectrogram
+      .into_iter()
+      .map(|bands| bands.map(|energy| Self::to_db(energy / max)))
+      .collect()
+  }
+
+  fn get_schroeder_curve(&self) -> Vec<f32> {
+    let mut remaining_energy = 0_f64;
+    let mut energy_decay: Vec<f64> = self
+      .samples
+      .iter()
+      .rev()
+      .map(|(left, right)| {
+        remaining_energy += (left * left + right * right) as f64;
+        remaining_energy
+      })
+      .collect();
+    energy_decay.reverse();
+
+    let total_energy = energy_decay
+      .first()
+      .copied()
+      .unwrap_or(0.)
+      .max(f64::MIN_POSITIVE);
+    energy_decay
+      .into_iter()
+      .map(|energy| Self::to_db((energy / total_energy) as f32))
+      .collect()
+  }
+
+  fn to_db(energy: f32) -> f32 {
+    if energy > 0. {
+      (energy.log10() * 10.).max(FLOOR)
+    } else {
+      FLOOR
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{ImpulseResponse, SPECTROGRAM_BANDS};
+  use std::f32::consts::TAU;
+
+  fn get_impulse_response(rt60: f32, freq: Option<f32>) -> ImpulseResponse {
+    let sample_rate = 44100.;
+    let mut rng = fastrand::Rng::with_seed(1);
+    // The amplitude falls by 60 dB over rt60 seconds
+    let decay = 0.001_f32.powf((sample_rate * rt60).recip());
+    let samples = (0..sample_rate as usize * 3)
+      .map(|i| {
+        let x = match freq {
+          Some(freq) => (i as f32 * freq * TAU / sample_rate).sin(),
+          None => rng.f32() * 2. - 1.,
+        } * decay.powi(i as i32);
+        (x, x)
+      })
+      .collect();
+
+    ImpulseResponse {
+      sample_rate,
+      samples,
+    }
+  }
+
+  #[test]
+  fn should_estimate_rt60() {
+    for rt60 in [0.5, 1., 1.5] {
+      let estimate = get_impulse_response(rt60, None).get_rt60().unwrap();
+      assert!((estimate - rt60).abs() < 0.05);
+    }
+    assert_eq!(get_impulse_response(20., None).get_rt60(), None);
+  }
+
+  #[test]
+  fn should_get_a_falling_energy_decay() {
+    let energy_decay = get_impulse_response(1., None).get_energy_decay(64);
+    assert_eq!(energy_decay.len(), 64);
+    assert_eq!(energy_decay[0], 0.);
+    assert!(energy_decay.windows(2).all(|x| x[1] <= x[0]));
+  }
+
+  #[test]
+  fn should_find_the_band_of_a_tone() {
+    let spectrogram = get_impulse_response(1., Some(1000.)).get_spectrogram(16);
+    assert_eq!(spectrogram.len(), 16);
+    let loudest_band = spectrogram[0]
+      .iter()
+      .enumerate()
+      .fold(0, |loudest, (i, level)| {
+        if *level > spectrogram[0][loudest] {
+          i
+        } else {
+          loudest
+        }
+      });
+    assert_eq!(SPECTROGRAM_BANDS[loudest_band], 1000.);
+    assert!(spectrogram[15][loudest_band] < spectrogram[0][loudest_band] - 30.);
+  }
+}
@@ -0,0 +1,175 @@
+use crate::shared::{bilinear_transform::BilinearTransform, biquad_filter::BiquadFilter};
+
+const BUTTERWORTH_Q: [f32; 2] = [0.541196, 1.306563];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Slope {
+  TwelveDb,
+  TwentyFourDb,
+}
+
+impl From<i32> for Slope {
+  fn from(value: i32) -> Self {
+    match value {
+      1 => Slope::TwentyFourDb,
+      _ => Slope::TwelveDb,
+    }
+  }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FilterType {
+  HighPass,
+  LowPass,
+}
+
+struct CutFilter {
+  filter_type: FilterType,
+  biquad_filters: [BiquadFilter; 2],
+  coefficients: [([f32; 3], [f32; 3]); 2],
+  freq: f32,
+  resonance: f32,
+  slope: Slope,
+}
+
+impl CutFilter {
+  fn new(filter_type: FilterType) -> Self {
+    Self {
+      filter_type,
+      biquad_filters: [BiquadFilter::new(), BiquadFilter::new()],
+      coefficients: [([1., 0., 0.], [1., 0., 0.]); 2],
+      freq: 0.,
+      resonance: 0.,
+      slope: Slope::TwelveDb,
+    }
+  }
+
+  fn reset(&mut self) {
+    self.biquad_filters.iter_mut().for_each(BiquadFilter::reset);
+  }
+
+  fn process(
+    &mut self,
+    input: (f32, f32),
+    freq: f32,
+    resonance: f32,
+    slope: Slope,
+    bilinear_transform: &BilinearTransform,
+  ) -> (f32, f32) {
+    if freq != self.freq || resonance != self.resonance || slope != self.slope {
+      self.freq = freq;
+      self.resonance = resonance;
+      self.slope = slope;
+      self.coefficients = match slope {
+        Slope::TwelveDb => [self.get_z_domain_coefficients(freq, resonance, bilinear_transform); 2],
+        Slope::TwentyFourDb => [
+          self.get_z_domain_coefficients(freq, BUTTERWORTH_Q[0], bilinear_transform),
+          self.get_z_domain_coefficients(
+            freq,
+            resonance * BUTTERWORTH_Q[1] * 2_f32.sqrt(),
+            bilinear_transform,
+          ),
+        ],
+      };
+    }
+
+    let output = self.biquad_filters[0].process(input, self.coefficients[0]);
+    match slope {
+      Slope::TwelveDb => output,
+      Slope::TwentyFourDb => self.biquad_filters[1].process(output, self.coefficients[1]),
+    }
+  }
+
+  fn get_z_domain_coefficients(
+    &self,
+    freq: f32,
+    q: f32,
+    bilinear_transform: &BilinearTransform,
+  ) -> ([f32; 3], [f32; 3]) {
+    let w = bilinear_transform.prewarp(freq);
+    let w_squared = w * w;
+    let a = [1., w / q, w_squared];
+    let b = match self.filter_type {
+      FilterType::HighPass => [1., 0., 0.],
+      FilterType::LowPass => [0., 0., w_squared],
+    };
+
+    bilinear_transform.process((b, a))
+  }
+}
+
+pub struct InputFilter {
+  bilinear_transform: BilinearTransform,
+  low_cut: CutFilter,
+  high_cut: CutFilter,
+}
+
+impl InputFilter {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      bilinear_transform: BilinearTransform::new(sample_rate),
+      low_cut: CutFilter::new(FilterType::HighPass),
+      high_cut: CutFilter::new(FilterType::LowPass),
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.low_cut.reset();
+    self.high_cut.reset();
+  }
+
+  pub fn process(
+    &mut self,
+    input: (f32, f32),
+    (low_cut, low_cut_resonance, low_cut_slope): (f32, f32, Slope),
+    (high_cut, high_cut_resonance, high_cut_slope): (f32, f32, Slope),
+  ) -> (f32, f32) {
+    let low_cut_output = self.low_cut.process(
+      input,
+      low_cut,
+      low_cut_resonance,
+      low_cut_slope,
+      &self.bilinear_transform,
+    );
+    self.high_cut.process(
+      low_cut_output,
+      high_cut,
+      high_cut_resonance,
+      high_cut_slope,
+      &self.bilinear_transform,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{InputFilter, Slope};
+  use crate::shared::sine_gain::get_sine_gain;
+  use std::f32::consts::FRAC_1_SQRT_2;
+
+  fn get_gain(freq: f32, low_cut: f32, high_cut: f32, slope: Slope) -> f32 {
+    let mut input_filter = InputFilter::new(44100.);
+    get_sine_gain(44100., freq, |x| {
+      input_filter
+        .process(
+          (x, x),
+          (low_cut, FRAC_1_SQRT_2, slope),
+          (high_cut, FRAC_1_SQRT_2, slope),
+        )
+        .0
+    })
+  }
+
+  #[test]
+  fn should_pass_frequencies_between_cutoffs() {
+    assert!((get_gain(1000., 20., 20000., Slope::TwelveDb) - 1.).abs() < 0.01);
+    assert!((get_gain(1000., 20., 20000., Slope::TwentyFourDb) - 1.).abs() < 0.01);
+  }
+
+  #[test]
+  fn should_attenuate_at_cutoffs() {
+    assert!((get_gain(200., 200., 20000., Slope::TwelveDb) - FRAC_1_SQRT_2).abs() < 0.01);
+    assert!((get_gain(5000., 20., 5000., Slope::TwentyFourDb) - FRAC_1_SQRT_2).abs() < 0.01);
+    assert!(get_gain(50., 400., 20000., Slope::TwentyFourDb) < 0.001);
+  }
+}
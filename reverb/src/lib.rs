@@ -1,66 +1,335 @@
 #![feature(portable_simd)]
 pub mod shared {
+  pub mod bilinear_transform;
+  pub mod biquad_filter;
   pub mod constants;
   pub mod delay_line;
   pub mod float_ext;
   pub mod phasor;
+  pub mod quality;
+  #[cfg(test)]
+  pub mod sine_gain;
   pub mod stereo_delay_line;
 }
+mod frequency_response;
+mod impulse_response;
+mod input_filter;
 mod mix;
+mod output_eq;
 mod params;
 mod predelay;
+mod reverse_tail;
+mod stereo_width;
 mod taps;
 mod tilt_filter;
-use {mix::Mix, params::Smoother, predelay::PreDelay, tilt_filter::TiltFilter};
+use {
+  input_filter::InputFilter,
+  mix::Mix,
+  output_eq::OutputEq,
+  params::Smoother,
+  predelay::PreDelay,
+  reverse_tail::ReverseTail,
+  stereo_width::StereoWidth,
+};
 pub use {
-  params::Params,
-  taps::{EarlyReflections, Taps},
+  frequency_response::get_frequency_response,
+  impulse_response::{ImpulseResponse, SPECTROGRAM_BANDS},
+  output_eq::EqBands,
+  params::{Params, Settings},
+  taps::{
+    EarlyReflections, GrainWindow, LfoShape, Reflection, ReflectionPattern, RoomGeometry,
+    RoomReflections, ShimmerMode, Taps, TapsParams,
+  },
+  tilt_filter::TiltFilter,
 };
 
 pub struct Reverb {
   predelay: PreDelay,
+  input_filter: InputFilter,
   taps: Taps,
+  reverse_tail: ReverseTail,
   tilt_filter: TiltFilter,
+  early_tilt_filter: TiltFilter,
+  output_eq: OutputEq,
+  early_output_eq: OutputEq,
+  stereo_width: StereoWidth,
+  early_stereo_width: StereoWidth,
   mix: Mix,
+  wet: (f32, f32),
 }
 
 impl Reverb {
   pub fn new(sample_rate: f32) -> Self {
     Self {
       predelay: PreDelay::new(sample_rate),
+      input_filter: InputFilter::new(sample_rate),
       taps: Taps::new(sample_rate),
+      reverse_tail: ReverseTail::new(sample_rate),
       tilt_filter: TiltFilter::new(sample_rate),
-      mix: Mix::new(),
+      early_tilt_filter: TiltFilter::new(sample_rate),
+      output_eq: OutputEq::new(sample_rate),
+      early_output_eq: OutputEq::new(sample_rate),
+      stereo_width: StereoWidth::new(sample_rate),
+      early_stereo_width: StereoWidth::new(sample_rate),
+      mix: Mix::new(sample_rate),
+      wet: (0., 0.),
     }
   }
 
+  /// Clears the buffers and filter states and restarts the modulation from the current seed, so the same input
+  /// renders the same output after every reset. It doesn't allocate, so it can be called from the audio thread.
+  pub fn reset(&mut self) {
+    self.predelay.reset();
+    self.input_filter.reset();
+    self.taps.reset();
+    self.reverse_tail.reset();
+    self.tilt_filter.reset();
+    self.early_tilt_filter.reset();
+    self.output_eq.reset();
+    self.early_output_eq.reset();
+    self.stereo_width.reset();
+    self.early_stereo_width.reset();
+    self.mix.reset();
+    self.wet = (0., 0.);
+  }
+
+  pub fn set_room_reflections(&mut self, room_reflections: RoomReflections) {
+    self.taps.set_room_reflections(room_reflections);
+  }
+
   pub fn process(&mut self, input: (f32, f32), params: &mut Params) -> (f32, f32) {
+    let (early_reflections, delay_network) = self.process_taps(input, params);
+    let tilt = params.tilt.next();
+    let eq = Self::get_eq_params(params);
+    let width = params.width.next();
+    let mono_bass = (params.mono_bass, params.mono_bass_freq.next());
+    let mix = params.mix.next();
+    let levels = (params.dry_level.next(), params.wet_level.next());
+
+    let tilt_filter_output = self.tilt_filter.process(
+      (
+        early_reflections.0 + delay_network.0,
+        early_reflections.1 + delay_network.1,
+      ),
+      tilt,
+    );
+    let output_eq_output = match eq {
+      Some((low_shelf, peak, high_shelf)) => {
+        self
+          .output_eq
+          .process(tilt_filter_output, low_shelf, peak, high_shelf)
+      }
+      None => tilt_filter_output,
+    };
+    let stereo_width_output = self
+      .stereo_width
+      .process(output_eq_output, width, mono_bass);
+    self.wet = stereo_width_output;
+    self.mix.process(
+      input,
+      stereo_width_output,
+      mix,
+      params.mix_mode,
+      levels,
+      params.kill_dry,
+    )
+  }
+
+  /// Returns the main output together with the processed early reflections and late tail.
+  pub fn process_with_buses(
+    &mut self,
+    input: (f32, f32),
+    params: &mut Params,
+  ) -> ((f32, f32), (f32, f32), (f32, f32)) {
+    let (early_reflections, delay_network) = self.process_taps(input, params);
+    let tilt = params.tilt.next();
+    let eq = Self::get_eq_params(params);
+    let width = params.width.next();
+    let mono_bass = (params.mono_bass, params.mono_bass_freq.next());
+    let mix = params.mix.next();
+    let levels = (params.dry_level.next(), params.wet_level.next());
+
+    let early = self.early_tilt_filter.process(early_reflections, tilt);
+    let late = self.tilt_filter.process(delay_network, tilt);
+    let (early, late) = match eq {
+      Some((low_shelf, peak, high_shelf)) => (
+        self
+          .early_output_eq
+          .process(early, low_shelf, peak, high_shelf),
+        self.output_eq.process(late, low_shelf, peak, high_shelf),
+      ),
+      None => (early, late),
+    };
+    let early = self.early_stereo_width.process(early, width, mono_bass);
+    let late = self.stereo_width.process(late, width, mono_bass);
+    self.wet = (early.0 + late.0, early.1 + late.1);
+    let output = self.mix.process(
+      input,
+      self.wet,
+      mix,
+      params.mix_mode,
+      levels,
+      params.kill_dry,
+    );
+    (output, early, late)
+  }
+
+  /// Returns the wet signal of the last processed sample, before it's mixed with the dry signal.
+  pub fn get_wet(&self) -> (f32, f32) {
+    self.wet
+  }
+
+  /// Returns the tank level and the gain compensation the tank applies to keep it from running away.
+  pub fn get_tank_levels(&self) -> (f32, f32) {
+    self.taps.get_tank_levels()
+  }
+
+  fn get_eq_params(params: &mut Params) -> Option<EqBands> {
+    let low_shelf = (params.low_shelf_freq.next(), params.low_shelf_gain.next());
+    let peak = (
+      params.peak_freq.next(),
+      params.peak_gain.next(),
+      params.peak_q.next(),
+    );
+    let high_shelf = (params.high_shelf_freq.next(), params.high_shelf_gain.next());
+    if params.eq {
+      Some((low_shelf, peak, high_shelf))
+    } else {
+      None
+    }
+  }
+
+  fn process_taps(&mut self, input: (f32, f32), params: &mut Params) -> ((f32, f32), (f32, f32)) {
     let reverse = params.reverse.next();
+    let reverse_crossfade = params.reverse_crossfade.next();
     let predelay = params.predelay.next();
     let size = params.size.next();
     let speed = params.speed;
     let depth = params.depth.next();
+    let lfo = (
+      params.lfo_shape,
+      params.lfo_phase_spread.next(),
+      params.lfo_rate_spread.next(),
+      params.lfo_seed,
+    );
+    let grains = (
+      params.grain_size.next(),
+      params.grain_density,
+      params.grain_window,
+      params.grain_curve.next(),
+      params.grain_pitch_jitter.next(),
+      params.grain_reverse.next(),
+    );
     let absorb = params.absorb.next();
     let decay = params.decay.next();
-    let tilt = params.tilt.next();
+    let decay_multipliers = (
+      params.low_decay.next(),
+      params.mid_decay.next(),
+      params.high_decay.next(),
+    );
+    let crossovers = (params.low_crossover.next(), params.high_crossover.next());
     let shimmer = params.shimmer.next();
-    let mix = params.mix.next();
+    let shimmer_voices = (
+      params.shimmer_pitch.next(),
+      params.shimmer_2_pitch.next(),
+      params.shimmer_2_level.next(),
+      params.shimmer_spread.next(),
+    );
+    let shimmer_mode = params.shimmer_mode;
+    let injection = (
+      params.injection_distribution.next(),
+      params.injection_rotation.next(),
+      params.injection_spread.next(),
+    );
+    let quality = params.quality;
+    let early = params.early;
+    let early_level = params.early_level.next();
+    let reverse_tail = params.reverse_tail.next();
+    let reverse_tail_length = params.reverse_tail_length.next();
+    let low_cut = (
+      params.low_cut.next(),
+      params.low_cut_resonance.next(),
+      params.low_cut_slope,
+    );
+    let high_cut = (
+      params.high_cut.next(),
+      params.high_cut_resonance.next(),
+      params.high_cut_slope,
+    );
     let diffuse = (absorb * 3.).min(1.) * 0.8;
     let absorb = (absorb - 0.3333333).max(0.) * 1.490214; // maximum is 0.993476 which equals a cutoff freq of 50Hz
 
-    let predelay_output = self.predelay.process(input, predelay, reverse);
-    let taps_output = self.taps.process(
-      predelay_output,
-      size,
-      speed,
-      depth,
-      diffuse,
-      absorb,
-      decay,
-      shimmer,
+    let predelay_output =
+      self
+        .predelay
+        .process(input, predelay, reverse, reverse_crossfade, quality);
+    let input_filter_output = self
+      .input_filter
+      .process(predelay_output, low_cut, high_cut);
+    let (early_reflections, delay_network) = self.taps.process(
+      input_filter_output,
+      &TapsParams {
+        size,
+        speed,
+        depth,
+        lfo,
+        grains,
+        diffuse,
+        absorb,
+        decay,
+        decay_multipliers,
+        crossovers,
+        shimmer,
+        shimmer_voices,
+        shimmer_mode,
+        injection,
+        quality,
+        early,
+        early_level,
+      },
     );
+    let reverse_tail_output =
+      self
+        .reverse_tail
+        .process(delay_network, reverse_tail, reverse_tail_length, quality);
+    (early_reflections, reverse_tail_output)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Params, Reverb, Settings};
+
+  fn render(reverb: &mut Reverb, params: &mut Params) -> Vec<(f32, f32)> {
+    (0..8000)
+      .map(|i| {
+        let input = if i % 2000 == 0 { 1. } else { 0. };
+        reverb.process((input, -input), params)
+      })
+      .collect()
+  }
+
+  #[test]
+  fn should_render_the_same_output_after_a_reset() {
+    let mut params = Params::new(44100.);
+    params.set(&Settings {
+      mix: 1.,
+      depth: 0.8,
+      lfo_shape: 3,
+      lfo_rate_spread: 0.5,
+      lfo_seed: 7,
+      grain_pitch_jitter: 80.,
+      grain_reverse: 0.4,
+      shimmer: 0.5,
+      reverse_tail: true,
+      ..Default::default()
+    });
+    let mut reverb = Reverb::new(44100.);
 
-    let tilt_filter_output = self.tilt_filter.process(taps_output, tilt);
-    self.mix.process(input, tilt_filter_output, mix)
+    let first = render(&mut reverb, &mut params);
+    reverb.reset();
+    let second = render(&mut reverb, &mut params);
+    assert!(first.iter().any(|(left, _)| *left != 0.));
+    assert_eq!(first, second);
   }
 }
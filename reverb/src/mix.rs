@@ -1,38 +1,108 @@
-use crate::shared::float_ext::FloatExt;
+use crate::{
+  params::{LinearSmooth, Smoother},
+  shared::float_ext::FloatExt,
+};
 use std::f32::consts::FRAC_PI_2;
 
+// Ramps the gains over 5 ms when the mode or kill dry switches them
+const GAIN_RAMP_FREQUENCY: f32 = 200.;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MixMode {
+  EqualPower,
+  Linear,
+  Independent,
+}
+
+impl From<i32> for MixMode {
+  fn from(value: i32) -> Self {
+    match value {
+      1 => MixMode::Linear,
+      2 => MixMode::Independent,
+      _ => MixMode::EqualPower,
+    }
+  }
+}
+
 pub struct Mix {
   mix: f32,
-  dry_gain: f32,
-  wet_gain: f32,
+  mix_mode: MixMode,
+  levels: (f32, f32),
+  kill_dry: bool,
+  dry_gain: LinearSmooth,
+  wet_gain: LinearSmooth,
+  is_initialized: bool,
 }
 
 impl Mix {
-  pub fn new() -> Self {
+  pub fn new(sample_rate: f32) -> Self {
     Self {
-      mix: 0.,
-      dry_gain: 1.,
-      wet_gain: 0.,
+      mix: f32::NAN,
+      mix_mode: MixMode::EqualPower,
+      levels: (0., 0.),
+      kill_dry: false,
+      dry_gain: LinearSmooth::new(sample_rate, GAIN_RAMP_FREQUENCY),
+      wet_gain: LinearSmooth::new(sample_rate, GAIN_RAMP_FREQUENCY),
+      is_initialized: false,
     }
   }
 
-  pub fn process(&mut self, dry: (f32, f32), wet: (f32, f32), mix: f32) -> (f32, f32) {
-    if mix != self.mix {
-      let factor = mix * FRAC_PI_2;
+  pub fn reset(&mut self) {
+    // The gains jump to their targets on the next sample
+    self.mix = f32::NAN;
+    self.is_initialized = false;
+  }
+
+  /// Crossfades between dry and wet with `mix`, unless the mode is independent. In that case `dry_level` and
+  /// `wet_level` set both gains in decibels. `kill_dry` removes the dry signal in every mode. The gains ramp to
+  /// their new values, so switching the mode or `kill_dry` while playing doesn't click.
+  pub fn process(
+    &mut self,
+    dry: (f32, f32),
+    wet: (f32, f32),
+    mix: f32,
+    mix_mode: MixMode,
+    (dry_level, wet_level): (f32, f32),
+    kill_dry: bool,
+  ) -> (f32, f32) {
+    if mix != self.mix
+      || mix_mode != self.mix_mode
+      || (dry_level, wet_level) != self.levels
+      || kill_dry != self.kill_dry
+    {
       self.mix = mix;
-      self.dry_gain = factor.fast_cos();
-      self.wet_gain = factor.fast_sin();
+      self.mix_mode = mix_mode;
+      self.levels = (dry_level, wet_level);
+      self.kill_dry = kill_dry;
+      let (dry_gain, wet_gain) = match mix_mode {
+        MixMode::EqualPower => {
+          let factor = mix * FRAC_PI_2;
+          (factor.fast_cos(), factor.fast_sin())
+        }
+        MixMode::Linear => (1. - mix, mix),
+        MixMode::Independent => (dry_level.dbtoa(), wet_level.dbtoa()),
+      };
+      let dry_gain = if kill_dry { 0. } else { dry_gain };
+      if !self.is_initialized {
+        self.dry_gain.reset(dry_gain);
+        self.wet_gain.reset(wet_gain);
+        self.is_initialized = true;
+      }
+      self.dry_gain.set_target(dry_gain);
+      self.wet_gain.set_target(wet_gain);
     }
+    let dry_gain = self.dry_gain.next();
+    let wet_gain = self.wet_gain.next();
     (
-      dry.0 * self.dry_gain + wet.0 * self.wet_gain,
-      dry.1 * self.dry_gain + wet.1 * self.wet_gain,
+      dry.0 * dry_gain + wet.0 * wet_gain,
+      dry.1 * dry_gain + wet.1 * wet_gain,
     )
   }
 }
 
 #[cfg(test)]
 mod tests {
-  use super::Mix;
+  use super::{Mix, MixMode};
 
   fn assert_approximately_eq(left: f32, right: f32, digits: usize) {
     let tol = 10f32.powi(-(digits as i32));
@@ -43,12 +113,28 @@ mod tests {
     );
   }
 
+  /// Runs past the gain ramp, which takes five samples at this sample rate.
+  fn get_settled_output(mut process: impl FnMut() -> (f32, f32)) -> (f32, f32) {
+    (0..8).fold((0., 0.), |_, _| process())
+  }
+
   #[test]
   fn mix() {
-    let mut mix = Mix::new();
-    let first = mix.process((0., 0.), (1., 1.), 0.);
-    let second = mix.process((0., 0.), (1., 1.), 0.5);
-    let third = mix.process((0., 0.), (1., 1.), 1.);
+    let mut mix = Mix::new(1000.);
+    let first = mix.process((0., 0.), (1., 1.), 0., MixMode::EqualPower, (0., 0.), false);
+    let second = get_settled_output(|| {
+      mix.process(
+        (0., 0.),
+        (1., 1.),
+        0.5,
+        MixMode::EqualPower,
+        (0., 0.),
+        false,
+      )
+    });
+    let third = get_settled_output(|| {
+      mix.process((0., 0.), (1., 1.), 1., MixMode::EqualPower, (0., 0.), false)
+    });
     assert_approximately_eq(first.0, 0., 3);
     assert_approximately_eq(first.1, 0., 3);
     assert_approximately_eq(second.0, 0.707, 3);
@@ -56,4 +142,62 @@ mod tests {
     assert_approximately_eq(third.0, 1., 3);
     assert_approximately_eq(third.1, 1., 3);
   }
+
+  #[test]
+  fn should_apply_mix_modes_and_kill_dry() {
+    let mut mix = Mix::new(1000.);
+    let linear = mix.process((1., 1.), (1., -1.), 0.25, MixMode::Linear, (0., 0.), false);
+    assert_eq!(linear, (1., 0.5));
+    let independent = get_settled_output(|| {
+      mix.process(
+        (1., 1.),
+        (1., 0.),
+        0.25,
+        MixMode::Independent,
+        (-6., 0.),
+        false,
+      )
+    });
+    assert_approximately_eq(independent.0, 1.501, 3);
+    assert_approximately_eq(independent.1, 0.501, 3);
+    let wet_only = get_settled_output(|| {
+      mix.process(
+        (1., 1.),
+        (0.5, 0.5),
+        0.5,
+        MixMode::EqualPower,
+        (0., 0.),
+        true,
+      )
+    });
+    assert_approximately_eq(wet_only.0, 0.354, 3);
+    let wet_only = get_settled_output(|| {
+      mix.process(
+        (1., 1.),
+        (0.5, 0.5),
+        0.,
+        MixMode::Independent,
+        (0., 0.),
+        true,
+      )
+    });
+    assert_eq!(wet_only, (0.5, 0.5));
+  }
+
+  #[test]
+  fn should_ramp_the_gains_when_kill_dry_switches() {
+    let mut mix = Mix::new(1000.);
+    let dry = mix.process((1., 1.), (0., 0.), 0., MixMode::Linear, (0., 0.), false);
+    assert_eq!(dry, (1., 1.));
+    let outputs: Vec<f32> = (0..6)
+      .map(|_| {
+        mix
+          .process((1., 1.), (0., 0.), 0., MixMode::Linear, (0., 0.), true)
+          .0
+      })
+      .collect();
+    assert!(outputs[0] > 0.5);
+    assert!(outputs.windows(2).all(|pair| pair[1] < pair[0]));
+    assert_eq!(outputs[5], 0.);
+  }
 }
@@ -0,0 +1,176 @@
+use crate::shared::{
+  bilinear_transform::BilinearTransform, biquad_filter::BiquadFilter, float_ext::FloatExt,
+};
+use std::f32::consts::FRAC_1_SQRT_2;
+
+/// Low shelf frequency and gain, peak frequency, gain and q and high shelf frequency and gain.
+pub type EqBands = ((f32, f32), (f32, f32, f32), (f32, f32));
+
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) enum BandType {
+  LowShelf,
+  HighShelf,
+  Peak,
+}
+
+impl BandType {
+  pub(crate) fn get_s_domain_coefficients(self, w: f32, gain: f32, q: f32) -> ([f32; 3], [f32; 3]) {
+    let a = (gain * 0.5).dbtoa();
+    let w_squared = w * w;
+
+    match self {
+      BandType::LowShelf => {
+        let w_over_q = a.sqrt() / q * w;
+        (
+          [a, a * w_over_q, a * a * w_squared],
+          [a, w_over_q, w_squared],
+        )
+      }
+      BandType::HighShelf => {
+        let w_over_q = a.sqrt() / q * w;
+        (
+          [a * a, a * w_over_q, a * w_squared],
+          [1., w_over_q, a * w_squared],
+        )
+      }
+      BandType::Peak => ([1., a / q * w, w_squared], [1., w / (a * q), w_squared]),
+    }
+  }
+}
+
+struct Band {
+  band_type: BandType,
+  biquad_filter: BiquadFilter,
+  coefficients: ([f32; 3], [f32; 3]),
+  freq: f32,
+  gain: f32,
+  q: f32,
+}
+
+impl Band {
+  fn new(band_type: BandType) -> Self {
+    Self {
+      band_type,
+      biquad_filter: BiquadFilter::new(),
+      coefficients: ([1., 0., 0.], [1., 0., 0.]),
+      freq: 0.,
+      gain: 0.,
+      q: 0.,
+    }
+  }
+
+  fn reset(&mut self) {
+    self.biquad_filter.reset();
+  }
+
+  fn process(
+    &mut self,
+    input: (f32, f32),
+    freq: f32,
+    gain: f32,
+    q: f32,
+    bilinear_transform: &BilinearTransform,
+  ) -> (f32, f32) {
+    if freq != self.freq || gain != self.gain || q != self.q {
+      self.freq = freq;
+      self.gain = gain;
+      self.q = q;
+      self.coefficients = bilinear_transform.process(self.band_type.get_s_domain_coefficients(
+        bilinear_transform.prewarp(freq),
+        gain,
+        q,
+      ));
+    }
+
+    self.biquad_filter.process(input, self.coefficients)
+  }
+}
+
+pub struct OutputEq {
+  bilinear_transform: BilinearTransform,
+  low_shelf: Band,
+  peak: Band,
+  high_shelf: Band,
+}
+
+impl OutputEq {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      bilinear_transform: BilinearTransform::new(sample_rate),
+      low_shelf: Band::new(BandType::LowShelf),
+      peak: Band::new(BandType::Peak),
+      high_shelf: Band::new(BandType::HighShelf),
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.low_shelf.reset();
+    self.peak.reset();
+    self.high_shelf.reset();
+  }
+
+  pub fn process(
+    &mut self,
+    input: (f32, f32),
+    (low_shelf_freq, low_shelf_gain): (f32, f32),
+    (peak_freq, peak_gain, peak_q): (f32, f32, f32),
+    (high_shelf_freq, high_shelf_gain): (f32, f32),
+  ) -> (f32, f32) {
+    let low_shelf_output = self.low_shelf.process(
+      input,
+      low_shelf_freq,
+      low_shelf_gain,
+      FRAC_1_SQRT_2,
+      &self.bilinear_transform,
+    );
+    let peak_output = self.peak.process(
+      low_shelf_output,
+      peak_freq,
+      peak_gain,
+      peak_q,
+      &self.bilinear_transform,
+    );
+    self.high_shelf.process(
+      peak_output,
+      high_shelf_freq,
+      high_shelf_gain,
+      FRAC_1_SQRT_2,
+      &self.bilinear_transform,
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::OutputEq;
+  use crate::shared::{float_ext::FloatExt, sine_gain::get_sine_gain};
+
+  fn get_gain(freq: f32, low_shelf_gain: f32, peak_gain: f32, high_shelf_gain: f32) -> f32 {
+    let mut output_eq = OutputEq::new(44100.);
+    get_sine_gain(44100., freq, |x| {
+      output_eq
+        .process(
+          (x, x),
+          (100., low_shelf_gain),
+          (1000., peak_gain, 2.),
+          (10000., high_shelf_gain),
+        )
+        .0
+    })
+  }
+
+  #[test]
+  fn should_be_transparent_without_gain() {
+    for freq in [50., 1000., 15000.] {
+      assert!((get_gain(freq, 0., 0., 0.) - 1.).abs() < 0.01);
+    }
+  }
+
+  #[test]
+  fn should_apply_band_gains() {
+    assert!((get_gain(20., -12., 0., 0.) - (-12_f32).dbtoa()).abs() < 0.02);
+    assert!((get_gain(1000., 0., 6., 0.) - 6_f32.dbtoa()).abs() < 0.02);
+    assert!((get_gain(20000., 0., 0., 9.) - 9_f32.dbtoa()).abs() < 0.05);
+    assert!((get_gain(1000., -12., 0., 9.) - 1.).abs() < 0.05);
+  }
+}
@@ -1,8 +1,23 @@
+mod settings;
 mod smooth;
+pub use settings::Settings;
 use smooth::ExponentialSmooth;
+pub(crate) use smooth::LinearSmooth;
 pub use smooth::Smoother;
 
-use crate::shared::constants::MAX_DEPTH;
+use crate::{
+  input_filter::Slope,
+  mix::MixMode,
+  reverse_tail::Division,
+  shared::{
+    constants::{
+      MAX_DEPTH, MAX_GRAIN_DENSITY, MAX_GRAIN_PITCH_JITTER, MAX_GRAIN_SIZE,
+      MAX_REVERSE_TAIL_LENGTH, MIN_GRAIN_DENSITY, MIN_GRAIN_SIZE, MIN_REVERSE_TAIL_LENGTH,
+    },
+    quality::Quality,
+  },
+  taps::{GrainWindow, LfoShape, ReflectionPattern, ShimmerMode},
+};
 
 pub struct Params {
   pub reverse: ExponentialSmooth,
@@ -15,6 +30,56 @@ pub struct Params {
   pub tilt: ExponentialSmooth,
   pub shimmer: ExponentialSmooth,
   pub mix: ExponentialSmooth,
+  pub quality: Quality,
+  pub early: ReflectionPattern,
+  pub early_level: ExponentialSmooth,
+  pub low_decay: ExponentialSmooth,
+  pub mid_decay: ExponentialSmooth,
+  pub high_decay: ExponentialSmooth,
+  pub low_crossover: ExponentialSmooth,
+  pub high_crossover: ExponentialSmooth,
+  pub low_cut: ExponentialSmooth,
+  pub low_cut_resonance: ExponentialSmooth,
+  pub low_cut_slope: Slope,
+  pub high_cut: ExponentialSmooth,
+  pub high_cut_resonance: ExponentialSmooth,
+  pub high_cut_slope: Slope,
+  pub eq: bool,
+  pub low_shelf_freq: ExponentialSmooth,
+  pub low_shelf_gain: ExponentialSmooth,
+  pub peak_freq: ExponentialSmooth,
+  pub peak_gain: ExponentialSmooth,
+  pub peak_q: ExponentialSmooth,
+  pub high_shelf_freq: ExponentialSmooth,
+  pub high_shelf_gain: ExponentialSmooth,
+  pub shimmer_pitch: ExponentialSmooth,
+  pub shimmer_2_pitch: ExponentialSmooth,
+  pub shimmer_2_level: ExponentialSmooth,
+  pub shimmer_spread: ExponentialSmooth,
+  pub shimmer_mode: ShimmerMode,
+  pub reverse_tail: ExponentialSmooth,
+  pub reverse_tail_length: ExponentialSmooth,
+  pub reverse_crossfade: ExponentialSmooth,
+  pub width: ExponentialSmooth,
+  pub mono_bass: bool,
+  pub mono_bass_freq: ExponentialSmooth,
+  pub injection_distribution: ExponentialSmooth,
+  pub injection_rotation: ExponentialSmooth,
+  pub injection_spread: ExponentialSmooth,
+  pub lfo_shape: LfoShape,
+  pub lfo_phase_spread: ExponentialSmooth,
+  pub lfo_rate_spread: ExponentialSmooth,
+  pub lfo_seed: u64,
+  pub grain_size: ExponentialSmooth,
+  pub grain_density: usize,
+  pub grain_window: GrainWindow,
+  pub grain_curve: ExponentialSmooth,
+  pub grain_pitch_jitter: ExponentialSmooth,
+  pub grain_reverse: ExponentialSmooth,
+  pub mix_mode: MixMode,
+  pub dry_level: ExponentialSmooth,
+  pub wet_level: ExponentialSmooth,
+  pub kill_dry: bool,
   is_initialized: bool,
 }
 
@@ -31,26 +96,158 @@ impl Params {
       tilt: ExponentialSmooth::new(sample_rate, 12.),
       shimmer: ExponentialSmooth::new(sample_rate, 12.),
       mix: ExponentialSmooth::new(sample_rate, 12.),
+      quality: Quality::Standard,
+      early: ReflectionPattern::Room,
+      early_level: ExponentialSmooth::new(sample_rate, 12.),
+      low_decay: ExponentialSmooth::new(sample_rate, 12.),
+      mid_decay: ExponentialSmooth::new(sample_rate, 12.),
+      high_decay: ExponentialSmooth::new(sample_rate, 12.),
+      low_crossover: ExponentialSmooth::new(sample_rate, 12.),
+      high_crossover: ExponentialSmooth::new(sample_rate, 12.),
+      low_cut: ExponentialSmooth::new(sample_rate, 12.),
+      low_cut_resonance: ExponentialSmooth::new(sample_rate, 12.),
+      low_cut_slope: Slope::TwelveDb,
+      high_cut: ExponentialSmooth::new(sample_rate, 12.),
+      high_cut_resonance: ExponentialSmooth::new(sample_rate, 12.),
+      high_cut_slope: Slope::TwelveDb,
+      eq: false,
+      low_shelf_freq: ExponentialSmooth::new(sample_rate, 12.),
+      low_shelf_gain: ExponentialSmooth::new(sample_rate, 12.),
+      peak_freq: ExponentialSmooth::new(sample_rate, 12.),
+      peak_gain: ExponentialSmooth::new(sample_rate, 12.),
+      peak_q: ExponentialSmooth::new(sample_rate, 12.),
+      high_shelf_freq: ExponentialSmooth::new(sample_rate, 12.),
+      high_shelf_gain: ExponentialSmooth::new(sample_rate, 12.),
+      shimmer_pitch: ExponentialSmooth::new(sample_rate, 12.),
+      shimmer_2_pitch: ExponentialSmooth::new(sample_rate, 12.),
+      shimmer_2_level: ExponentialSmooth::new(sample_rate, 12.),
+      shimmer_spread: ExponentialSmooth::new(sample_rate, 12.),
+      shimmer_mode: ShimmerMode::Classic,
+      reverse_tail: ExponentialSmooth::new(sample_rate, 12.),
+      reverse_tail_length: ExponentialSmooth::new(sample_rate, 12.),
+      reverse_crossfade: ExponentialSmooth::new(sample_rate, 12.),
+      width: ExponentialSmooth::new(sample_rate, 12.),
+      mono_bass: false,
+      mono_bass_freq: ExponentialSmooth::new(sample_rate, 12.),
+      injection_distribution: ExponentialSmooth::new(sample_rate, 12.),
+      injection_rotation: ExponentialSmooth::new(sample_rate, 12.),
+      injection_spread: ExponentialSmooth::new(sample_rate, 12.),
+      lfo_shape: LfoShape::Sine,
+      lfo_phase_spread: ExponentialSmooth::new(sample_rate, 12.),
+      lfo_rate_spread: ExponentialSmooth::new(sample_rate, 12.),
+      lfo_seed: 0,
+      grain_size: ExponentialSmooth::new(sample_rate, 12.),
+      grain_density: MIN_GRAIN_DENSITY,
+      grain_window: GrainWindow::Hann,
+      grain_curve: ExponentialSmooth::new(sample_rate, 12.),
+      grain_pitch_jitter: ExponentialSmooth::new(sample_rate, 12.),
+      grain_reverse: ExponentialSmooth::new(sample_rate, 12.),
+      mix_mode: MixMode::EqualPower,
+      dry_level: ExponentialSmooth::new(sample_rate, 12.),
+      wet_level: ExponentialSmooth::new(sample_rate, 12.),
+      kill_dry: false,
       is_initialized: false,
     }
   }
 
-  pub fn set(
-    &mut self,
-    reverse: f32,
-    predelay: f32,
-    size: f32,
-    speed: f32,
-    depth: f32,
-    absorb: f32,
-    decay: f32,
-    tilt: f32,
-    shimmer: f32,
-    mix: f32,
-  ) {
+  pub fn set(&mut self, settings: &Settings) {
+    let Settings {
+      reverse,
+      predelay,
+      size,
+      speed,
+      depth,
+      absorb,
+      decay,
+      tilt,
+      shimmer,
+      mix,
+      quality,
+      early,
+      early_level,
+      low_decay,
+      mid_decay,
+      high_decay,
+      low_crossover,
+      high_crossover,
+      low_cut,
+      low_cut_resonance,
+      low_cut_slope,
+      high_cut,
+      high_cut_resonance,
+      high_cut_slope,
+      eq,
+      low_shelf_freq,
+      low_shelf_gain,
+      peak_freq,
+      peak_gain,
+      peak_q,
+      high_shelf_freq,
+      high_shelf_gain,
+      shimmer_pitch,
+      shimmer_quantize,
+      shimmer_2_pitch,
+      shimmer_2_level,
+      shimmer_spread,
+      shimmer_mode,
+      reverse_tail,
+      reverse_tail_length,
+      reverse_tail_sync,
+      reverse_tail_division,
+      tempo,
+      reverse_crossfade,
+      width,
+      mono_bass,
+      mono_bass_freq,
+      injection_distribution,
+      injection_rotation,
+      injection_spread,
+      lfo_shape,
+      lfo_phase_spread,
+      lfo_rate_spread,
+      lfo_seed,
+      grain_size,
+      grain_density,
+      grain_window,
+      grain_curve,
+      grain_pitch_jitter,
+      grain_reverse,
+      mix_mode,
+      dry_level,
+      wet_level,
+      kill_dry,
+    } = *settings;
     self.speed = speed;
+    self.quality = quality.into();
+    self.early = early.into();
+    self.low_cut_slope = low_cut_slope.into();
+    self.high_cut_slope = high_cut_slope.into();
+    self.shimmer_mode = shimmer_mode.into();
+    self.lfo_shape = lfo_shape.into();
+    self.lfo_seed = lfo_seed.max(0) as u64;
+    self.grain_density =
+      (grain_density.max(0) as usize).clamp(MIN_GRAIN_DENSITY, MAX_GRAIN_DENSITY);
+    self.grain_window = grain_window.into();
+    self.mix_mode = mix_mode.into();
+    self.kill_dry = kill_dry;
+    let grain_size = grain_size.clamp(MIN_GRAIN_SIZE, MAX_GRAIN_SIZE);
+    let grain_pitch_jitter = grain_pitch_jitter.clamp(0., MAX_GRAIN_PITCH_JITTER);
+    self.mono_bass = mono_bass;
+    self.eq = eq;
     let depth = depth * depth.abs() * MAX_DEPTH;
-    let tilt = tilt * tilt.abs() * 0.5 + 0.5;
+    let tilt = Self::map_tilt(tilt);
+    let (shimmer_pitch, shimmer_2_pitch) = if shimmer_quantize {
+      (shimmer_pitch.round(), shimmer_2_pitch.round())
+    } else {
+      (shimmer_pitch, shimmer_2_pitch)
+    };
+    let reverse_tail = if reverse_tail { 1. } else { 0. };
+    let reverse_tail_length = if reverse_tail_sync {
+      Division::from(reverse_tail_division).to_ms(tempo)
+    } else {
+      reverse_tail_length
+    }
+    .clamp(MIN_REVERSE_TAIL_LENGTH, MAX_REVERSE_TAIL_LENGTH);
 
     if self.is_initialized {
       self.reverse.set_target(reverse);
@@ -62,6 +259,45 @@ impl Params {
       self.tilt.set_target(tilt);
       self.shimmer.set_target(shimmer);
       self.mix.set_target(mix);
+      self.early_level.set_target(early_level);
+      self.low_decay.set_target(low_decay);
+      self.mid_decay.set_target(mid_decay);
+      self.high_decay.set_target(high_decay);
+      self.low_crossover.set_target(low_crossover);
+      self.high_crossover.set_target(high_crossover);
+      self.low_cut.set_target(low_cut);
+      self.low_cut_resonance.set_target(low_cut_resonance);
+      self.high_cut.set_target(high_cut);
+      self.high_cut_resonance.set_target(high_cut_resonance);
+      self.low_shelf_freq.set_target(low_shelf_freq);
+      self.low_shelf_gain.set_target(low_shelf_gain);
+      self.peak_freq.set_target(peak_freq);
+      self.peak_gain.set_target(peak_gain);
+      self.peak_q.set_target(peak_q);
+      self.high_shelf_freq.set_target(high_shelf_freq);
+      self.high_shelf_gain.set_target(high_shelf_gain);
+      self.shimmer_pitch.set_target(shimmer_pitch);
+      self.shimmer_2_pitch.set_target(shimmer_2_pitch);
+      self.shimmer_2_level.set_target(shimmer_2_level);
+      self.shimmer_spread.set_target(shimmer_spread);
+      self.reverse_tail.set_target(reverse_tail);
+      self.reverse_tail_length.set_target(reverse_tail_length);
+      self.reverse_crossfade.set_target(reverse_crossfade);
+      self.width.set_target(width);
+      self.mono_bass_freq.set_target(mono_bass_freq);
+      self
+        .injection_distribution
+        .set_target(injection_distribution);
+      self.injection_rotation.set_target(injection_rotation);
+      self.injection_spread.set_target(injection_spread);
+      self.lfo_phase_spread.set_target(lfo_phase_spread);
+      self.lfo_rate_spread.set_target(lfo_rate_spread);
+      self.grain_size.set_target(grain_size);
+      self.grain_curve.set_target(grain_curve);
+      self.grain_pitch_jitter.set_target(grain_pitch_jitter);
+      self.grain_reverse.set_target(grain_reverse);
+      self.dry_level.set_target(dry_level);
+      self.wet_level.set_target(wet_level);
     } else {
       self.reverse.reset(reverse);
       self.predelay.reset(predelay);
@@ -72,7 +308,49 @@ impl Params {
       self.tilt.reset(tilt);
       self.shimmer.reset(shimmer);
       self.mix.reset(mix);
+      self.early_level.reset(early_level);
+      self.low_decay.reset(low_decay);
+      self.mid_decay.reset(mid_decay);
+      self.high_decay.reset(high_decay);
+      self.low_crossover.reset(low_crossover);
+      self.high_crossover.reset(high_crossover);
+      self.low_cut.reset(low_cut);
+      self.low_cut_resonance.reset(low_cut_resonance);
+      self.high_cut.reset(high_cut);
+      self.high_cut_resonance.reset(high_cut_resonance);
+      self.low_shelf_freq.reset(low_shelf_freq);
+      self.low_shelf_gain.reset(low_shelf_gain);
+      self.peak_freq.reset(peak_freq);
+      self.peak_gain.reset(peak_gain);
+      self.peak_q.reset(peak_q);
+      self.high_shelf_freq.reset(high_shelf_freq);
+      self.high_shelf_gain.reset(high_shelf_gain);
+      self.shimmer_pitch.reset(shimmer_pitch);
+      self.shimmer_2_pitch.reset(shimmer_2_pitch);
+      self.shimmer_2_level.reset(shimmer_2_level);
+      self.shimmer_spread.reset(shimmer_spread);
+      self.reverse_tail.reset(reverse_tail);
+      self.reverse_tail_length.reset(reverse_tail_length);
+      self.reverse_crossfade.reset(reverse_crossfade);
+      self.width.reset(width);
+      self.mono_bass_freq.reset(mono_bass_freq);
+      self.injection_distribution.reset(injection_distribution);
+      self.injection_rotation.reset(injection_rotation);
+      self.injection_spread.reset(injection_spread);
+      self.lfo_phase_spread.reset(lfo_phase_spread);
+      self.lfo_rate_spread.reset(lfo_rate_spread);
+      self.grain_size.reset(grain_size);
+      self.grain_curve.reset(grain_curve);
+      self.grain_pitch_jitter.reset(grain_pitch_jitter);
+      self.grain_reverse.reset(grain_reverse);
+      self.dry_level.reset(dry_level);
+      self.wet_level.reset(wet_level);
       self.is_initialized = true;
     }
   }
+
+  /// Maps the bipolar tilt param to the position of the tilt potentiometer.
+  pub(crate) fn map_tilt(tilt: f32) -> f32 {
+    tilt * tilt.abs() * 0.5 + 0.5
+  }
 }
@@ -0,0 +1,142 @@
+use crate::shared::constants::{MIN_GRAIN_DENSITY, MIN_PREDELAY};
+
+/// The unsmoothed param values a wrapper passes to `Params::set`. Enums are passed as their index and are
+/// converted, clamped and smoothed by `Params::set`. `Default` matches the defaults of the plugin params.
+#[derive(Clone, Copy, PartialEq)]
+pub struct Settings {
+  pub reverse: f32,
+  pub predelay: f32,
+  pub size: f32,
+  pub speed: f32,
+  pub depth: f32,
+  pub absorb: f32,
+  pub decay: f32,
+  pub tilt: f32,
+  pub shimmer: f32,
+  pub mix: f32,
+  pub quality: i32,
+  pub early: i32,
+  pub early_level: f32,
+  pub low_decay: f32,
+  pub mid_decay: f32,
+  pub high_decay: f32,
+  pub low_crossover: f32,
+  pub high_crossover: f32,
+  pub low_cut: f32,
+  pub low_cut_resonance: f32,
+  pub low_cut_slope: i32,
+  pub high_cut: f32,
+  pub high_cut_resonance: f32,
+  pub high_cut_slope: i32,
+  pub eq: bool,
+  pub low_shelf_freq: f32,
+  pub low_shelf_gain: f32,
+  pub peak_freq: f32,
+  pub peak_gain: f32,
+  pub peak_q: f32,
+  pub high_shelf_freq: f32,
+  pub high_shelf_gain: f32,
+  pub shimmer_pitch: f32,
+  pub shimmer_quantize: bool,
+  pub shimmer_2_pitch: f32,
+  pub shimmer_2_level: f32,
+  pub shimmer_spread: f32,
+  pub shimmer_mode: i32,
+  pub reverse_tail: bool,
+  pub reverse_tail_length: f32,
+  pub reverse_tail_sync: bool,
+  pub reverse_tail_division: i32,
+  pub tempo: f32,
+  pub reverse_crossfade: f32,
+  pub width: f32,
+  pub mono_bass: bool,
+  pub mono_bass_freq: f32,
+  pub injection_distribution: f32,
+  pub injection_rotation: f32,
+  pub injection_spread: f32,
+  pub lfo_shape: i32,
+  pub lfo_phase_spread: f32,
+  pub lfo_rate_spread: f32,
+  pub lfo_seed: i32,
+  pub grain_size: f32,
+  pub grain_density: i32,
+  pub grain_window: i32,
+  pub grain_curve: f32,
+  pub grain_pitch_jitter: f32,
+  pub grain_reverse: f32,
+  pub mix_mode: i32,
+  pub dry_level: f32,
+  pub wet_level: f32,
+  pub kill_dry: bool,
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self {
+      reverse: 0.,
+      predelay: MIN_PREDELAY,
+      size: 80.,
+      speed: 2.,
+      depth: -0.1,
+      absorb: 0.5,
+      decay: 0.9,
+      tilt: 0.,
+      shimmer: 0.,
+      mix: 0.5,
+      quality: 1,
+      early: 0,
+      early_level: 1.,
+      low_decay: 1.,
+      mid_decay: 1.,
+      high_decay: 1.,
+      low_crossover: 250.,
+      high_crossover: 4000.,
+      low_cut: 20.,
+      low_cut_resonance: 0.707,
+      low_cut_slope: 0,
+      high_cut: 20000.,
+      high_cut_resonance: 0.707,
+      high_cut_slope: 0,
+      eq: false,
+      low_shelf_freq: 200.,
+      low_shelf_gain: 0.,
+      peak_freq: 1000.,
+      peak_gain: 0.,
+      peak_q: 0.707,
+      high_shelf_freq: 6000.,
+      high_shelf_gain: 0.,
+      shimmer_pitch: 12.,
+      shimmer_quantize: true,
+      shimmer_2_pitch: 19.,
+      shimmer_2_level: 0.,
+      shimmer_spread: 0.,
+      shimmer_mode: 0,
+      reverse_tail: false,
+      reverse_tail_length: 500.,
+      reverse_tail_sync: false,
+      reverse_tail_division: 2,
+      tempo: 120.,
+      reverse_crossfade: MIN_PREDELAY,
+      width: 1.,
+      mono_bass: false,
+      mono_bass_freq: 120.,
+      injection_distribution: 0.,
+      injection_rotation: 0.,
+      injection_spread: 1.,
+      lfo_shape: 0,
+      lfo_phase_spread: 1.,
+      lfo_rate_spread: 0.,
+      lfo_seed: 0,
+      grain_size: 500.,
+      grain_density: MIN_GRAIN_DENSITY as i32,
+      grain_window: 0,
+      grain_curve: 0.,
+      grain_pitch_jitter: 0.,
+      grain_reverse: 0.,
+      mix_mode: 0,
+      dry_level: 0.,
+      wet_level: 0.,
+      kill_dry: false,
+    }
+  }
+}
@@ -1,7 +1,8 @@
 mod reverse;
 use crate::shared::{
   constants::{MAX_PREDELAY, MIN_PREDELAY},
-  stereo_delay_line::{Interpolation, StereoDelayLine},
+  quality::Quality,
+  stereo_delay_line::StereoDelayLine,
 };
 use reverse::Reverse;
 
@@ -21,15 +22,33 @@ impl PreDelay {
     }
   }
 
-  pub fn process(&mut self, input: (f32, f32), time: f32, reverse: f32) -> (f32, f32) {
+  pub fn reset(&mut self) {
+    self.delay_line.reset();
+    self.reverse.reset();
+  }
+
+  pub fn process(
+    &mut self,
+    input: (f32, f32),
+    time: f32,
+    reverse: f32,
+    crossfade: f32,
+    quality: Quality,
+  ) -> (f32, f32) {
+    let interp = quality.static_interpolation();
+    let reverse_interp = quality.modulated_interpolation();
     let predelay_output = if reverse == 0. {
-      self.delay_line.read(time, Interpolation::Linear)
+      self.delay_line.read(time, interp)
     } else if reverse == 1. {
-      self.reverse.process(&self.delay_line, time)
+      self
+        .reverse
+        .process(&self.delay_line, time, crossfade, reverse_interp)
     } else {
       Self::mix(
-        self.delay_line.read(time, Interpolation::Linear),
-        self.reverse.process(&self.delay_line, time),
+        self.delay_line.read(time, interp),
+        self
+          .reverse
+          .process(&self.delay_line, time, crossfade, reverse_interp),
         reverse,
       )
     };
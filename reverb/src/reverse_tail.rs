@@ -0,0 +1,188 @@
+use crate::shared::{
+  constants::MAX_REVERSE_TAIL_LENGTH,
+  phasor::Phasor,
+  quality::Quality,
+  stereo_delay_line::{Interpolation, StereoDelayLine},
+};
+
+const CROSSFADE_TIME: f32 = 10.;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Division {
+  Sixteenth,
+  Eighth,
+  Quarter,
+  Half,
+  Whole,
+  TwoBars,
+}
+
+impl Division {
+  pub fn to_ms(self, tempo: f32) -> f32 {
+    let beats = match self {
+      Division::Sixteenth => 0.25,
+      Division::Eighth => 0.5,
+      Division::Quarter => 1.,
+      Division::Half => 2.,
+      Division::Whole => 4.,
+      Division::TwoBars => 8.,
+    };
+    beats * 60000. / tempo.max(1.)
+  }
+}
+
+impl From<i32> for Division {
+  fn from(value: i32) -> Self {
+    match value {
+      0 => Division::Sixteenth,
+      1 => Division::Eighth,
+      3 => Division::Half,
+      4 => Division::Whole,
+      5 => Division::TwoBars,
+      _ => Division::Quarter,
+    }
+  }
+}
+
+/// Plays the tail back reversed in segments of half `length`, with two read heads that take turns
+/// every segment, so the tail swells up instead of decaying.
+pub struct ReverseTail {
+  delay_line: StereoDelayLine,
+  phasor: Phasor,
+}
+
+impl ReverseTail {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      delay_line: StereoDelayLine::new(
+        (sample_rate * (MAX_REVERSE_TAIL_LENGTH + CROSSFADE_TIME) * 2. / 1000.) as usize,
+        sample_rate,
+      ),
+      phasor: Phasor::new(sample_rate),
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.delay_line.reset();
+    self.phasor.reset();
+  }
+
+  pub fn process(
+    &mut self,
+    input: (f32, f32),
+    amount: f32,
+    length: f32,
+    quality: Quality,
+  ) -> (f32, f32) {
+    let output = if amount == 0. {
+      input
+    } else {
+      let reversed = self.read_reversed(length, quality.modulated_interpolation());
+      (
+        input.0 + (reversed.0 - input.0) * amount,
+        input.1 + (reversed.1 - input.1) * amount,
+      )
+    };
+    self.delay_line.write(input);
+    output
+  }
+
+  fn read_reversed(&mut self, length: f32, interp: Interpolation) -> (f32, f32) {
+    // Each read head completes a cycle every segment and is heard during the first half of it. Its delay grows at
+    // twice the speed of time, so it plays back in reverse.
+    let phasor_a = self.phasor.process(1000. / length) * 2.;
+    let phasor_b = Self::wrap(phasor_a + 1.);
+
+    let xfade_factor = length / CROSSFADE_TIME;
+    let head_a = self.read_delay_line(phasor_a, length, xfade_factor, interp);
+    let head_b = self.read_delay_line(phasor_b, length, xfade_factor, interp);
+    (head_a.0 + head_b.0, head_a.1 + head_b.1)
+  }
+
+  fn read_delay_line(
+    &self,
+    phasor: f32,
+    length: f32,
+    xfade_factor: f32,
+    interp: Interpolation,
+  ) -> (f32, f32) {
+    let ramp_up = (phasor * xfade_factor).min(1.);
+    let ramp_down = ((1. - phasor) * xfade_factor + 1.).clamp(0., 1.);
+    let swell = phasor.min(1.);
+    let gain = ramp_up * ramp_down * swell;
+
+    if gain == 0. {
+      (0., 0.)
+    } else {
+      let delay_out = self.delay_line.read(phasor * length, interp);
+      (delay_out.0 * gain, delay_out.1 * gain)
+    }
+  }
+
+  fn wrap(x: f32) -> f32 {
+    if x >= 2. {
+      x - 2.
+    } else {
+      x
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Division, ReverseTail};
+  use crate::shared::quality::Quality;
+
+  fn get_extreme(output: &[f32], is_beyond: impl Fn(f32, f32) -> bool) -> (usize, f32) {
+    output
+      .iter()
+      .enumerate()
+      .fold((0, 0.), |(extreme_index, extreme), (i, &x)| {
+        if is_beyond(x, extreme) {
+          (i, x)
+        } else {
+          (extreme_index, extreme)
+        }
+      })
+  }
+
+  #[test]
+  fn should_convert_divisions_to_ms() {
+    assert_eq!(Division::Quarter.to_ms(120.), 500.);
+    assert_eq!(Division::TwoBars.to_ms(120.), 4000.);
+    assert_eq!(Division::Sixteenth.to_ms(60.), 250.);
+  }
+
+  #[test]
+  fn should_play_each_segment_in_reverse() {
+    let sample_rate = 1000.;
+    let mut reverse_tail = ReverseTail::new(sample_rate);
+    let output: Vec<f32> = (0..1000)
+      .map(|i| {
+        // Both impulses fall within the segment that the second read head starts playing at sample 549
+        let input = match i {
+          510 => 1.,
+          520 => -1.,
+          _ => 0.,
+        };
+        reverse_tail
+          .process((input, input), 1., 100., Quality::Standard)
+          .0
+      })
+      .collect();
+
+    let (min_index, min) = get_extreme(&output[521..], |a, b| a < b);
+    let (max_index, max) = get_extreme(&output[521..], |a, b| a > b);
+    assert!(min < -0.1 && max > 0.1);
+    assert_eq!(max_index - min_index, 10);
+  }
+
+  #[test]
+  fn should_pass_input_when_disabled() {
+    let mut reverse_tail = ReverseTail::new(44100.);
+    assert_eq!(
+      reverse_tail.process((0.3, -0.2), 0., 500., Quality::Standard),
+      (0.3, -0.2)
+    );
+  }
+}
@@ -1,4 +1,7 @@
+use std::f32::consts::PI;
+
 pub struct BilinearTransform {
+  sample_rate: f32,
   s: [f32; 2],
 }
 
@@ -6,10 +9,17 @@ impl BilinearTransform {
   pub fn new(sample_rate: f32) -> Self {
     let t = sample_rate.recip();
     Self {
+      sample_rate,
       s: [t / 2., t * t / 4.],
     }
   }
 
+  /// Returns the angular frequency that ends up at `freq` after the bilinear transform.
+  pub fn prewarp(&self, freq: f32) -> f32 {
+    let freq = freq.min(self.sample_rate * 0.45);
+    2. * self.sample_rate * (PI * freq / self.sample_rate).tan()
+  }
+
   fn bilinear_transform(&self, mut x: [f32; 3]) -> [f32; 3] {
     x[1] *= self.s[0];
     x[2] *= self.s[1];
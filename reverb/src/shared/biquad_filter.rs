@@ -1,3 +1,4 @@
+#[derive(Default)]
 pub struct BiquadFilter {
   z: [(f32, f32); 2],
 }
@@ -7,6 +8,10 @@ impl BiquadFilter {
     Self { z: [(0.0, 0.0); 2] }
   }
 
+  pub fn reset(&mut self) {
+    self.z = [(0.0, 0.0); 2];
+  }
+
   pub fn process(&mut self, x: (f32, f32), (b, a): ([f32; 3], [f32; 3])) -> (f32, f32) {
     let y = (x.0 * b[0] + self.z[0].0, x.1 * b[0] + self.z[0].1);
     self.z[0] = (
@@ -3,3 +3,12 @@ pub const MAX_PREDELAY: f32 = 500.;
 pub const MIN_SIZE: f32 = 1.;
 pub const MAX_SIZE: f32 = 500.;
 pub const MAX_DEPTH: f32 = 3.;
+pub const MIN_ROOM_DIMENSION: f32 = 1.;
+pub const MAX_ROOM_DIMENSION: f32 = 50.;
+pub const MIN_REVERSE_TAIL_LENGTH: f32 = 50.;
+pub const MAX_REVERSE_TAIL_LENGTH: f32 = 4000.;
+pub const MIN_GRAIN_SIZE: f32 = 10.;
+pub const MAX_GRAIN_SIZE: f32 = 1000.;
+pub const MIN_GRAIN_DENSITY: usize = 2;
+pub const MAX_GRAIN_DENSITY: usize = 8;
+pub const MAX_GRAIN_PITCH_JITTER: f32 = 100.;
@@ -1,3 +1,4 @@
+#[derive(Clone, Copy)]
 pub struct Phasor {
   sample_rate: f32,
   x: f32,
@@ -8,6 +9,10 @@ impl Phasor {
     Self { sample_rate, x: 0. }
   }
 
+  pub fn reset(&mut self) {
+    self.x = 0.;
+  }
+
   fn wrap(&self, input: f32) -> f32 {
     if input >= 1. {
       input - 1.
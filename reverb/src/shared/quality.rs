@@ -0,0 +1,46 @@
+use super::delay_line::Interpolation;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Quality {
+  Eco,
+  Standard,
+  High,
+}
+
+impl Quality {
+  /// The interpolation used for reads with a fixed delay time. Only these reads can step between samples
+  /// without zipper noise.
+  pub fn fixed_interpolation(&self) -> Interpolation {
+    match self {
+      Quality::Eco => Interpolation::Step,
+      Quality::Standard => Interpolation::Linear,
+      Quality::High => Interpolation::Spline,
+    }
+  }
+
+  /// The interpolation used for unmodulated reads with a smoothed delay time, like the size or predelay.
+  pub fn static_interpolation(&self) -> Interpolation {
+    match self {
+      Quality::Eco | Quality::Standard => Interpolation::Linear,
+      Quality::High => Interpolation::Spline,
+    }
+  }
+
+  /// The interpolation used for reads that are modulated by an lfo, grains or a phasor.
+  pub fn modulated_interpolation(&self) -> Interpolation {
+    match self {
+      Quality::Eco | Quality::Standard => Interpolation::Linear,
+      Quality::High => Interpolation::Spline,
+    }
+  }
+}
+
+impl From<i32> for Quality {
+  fn from(value: i32) -> Self {
+    match value {
+      0 => Quality::Eco,
+      2 => Quality::High,
+      _ => Quality::Standard,
+    }
+  }
+}
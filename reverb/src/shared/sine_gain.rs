@@ -0,0 +1,12 @@
+use std::f32::consts::TAU;
+
+/// Feeds one second of a full-scale sine at `freq` through `process` and returns the peak of the output over the
+/// second half, after the filters have settled.
+pub fn get_sine_gain(sample_rate: f32, freq: f32, mut process: impl FnMut(f32) -> f32) -> f32 {
+  let length = sample_rate as usize;
+
+  (0..length)
+    .map(|i| process((i as f32 * freq * TAU / sample_rate).sin()))
+    .skip(length / 2)
+    .fold(0., |peak: f32, x| peak.max(x.abs()))
+}
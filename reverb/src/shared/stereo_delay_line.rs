@@ -1,15 +1,6 @@
+pub use super::delay_line::Interpolation;
 use std::f32::consts::PI;
 
-#[allow(dead_code)]
-#[derive(Clone, Copy)]
-pub enum Interpolation {
-  Step,
-  Linear,
-  Cosine,
-  Cubic,
-  Spline,
-}
-
 #[derive(Clone)]
 pub struct StereoDelayLine {
   buffer: Vec<(f32, f32)>,
@@ -29,6 +20,11 @@ impl StereoDelayLine {
     }
   }
 
+  pub fn reset(&mut self) {
+    self.buffer.fill((0., 0.));
+    self.write_pointer = 0;
+  }
+
   pub fn read(&self, time: f32, interp: Interpolation) -> (f32, f32) {
     match interp {
       Interpolation::Step => self.step_interp(time),
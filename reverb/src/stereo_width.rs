@@ -0,0 +1,83 @@
+use std::f32::consts::TAU;
+
+pub struct StereoWidth {
+  t: f32,
+  z: f32,
+  freq: f32,
+  b1: f32,
+}
+
+impl StereoWidth {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      t: sample_rate.recip() * -TAU,
+      z: 0.,
+      freq: 0.,
+      b1: 0.,
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.z = 0.;
+  }
+
+  /// Scales the side signal by `width`, where 0 is mono, 1 leaves the image as is and 2 doubles the side signal.
+  /// When `mono_bass` is set, side content below `mono_bass_freq` is removed, so the output collapses to mono safely.
+  pub fn process(
+    &mut self,
+    (left, right): (f32, f32),
+    width: f32,
+    (mono_bass, mono_bass_freq): (bool, f32),
+  ) -> (f32, f32) {
+    let mid = (left + right) * 0.5;
+    let side = (left - right) * 0.5;
+
+    let side = if mono_bass {
+      if mono_bass_freq != self.freq {
+        self.freq = mono_bass_freq;
+        self.b1 = (mono_bass_freq * self.t).exp();
+      }
+      self.z = side * (1. - self.b1) + self.z * self.b1;
+      side - self.z
+    } else {
+      side
+    } * width;
+
+    (mid + side, mid - side)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::StereoWidth;
+  use crate::shared::sine_gain::get_sine_gain;
+
+  fn get_side_gain(freq: f32, width: f32, mono_bass: bool) -> f32 {
+    let mut stereo_width = StereoWidth::new(44100.);
+    get_sine_gain(44100., freq, |x| {
+      let (left, right) = stereo_width.process((x, -x), width, (mono_bass, 200.));
+      (left - right) * 0.5
+    })
+  }
+
+  #[test]
+  fn should_scale_side_signal() {
+    let mut stereo_width = StereoWidth::new(44100.);
+    assert_eq!(
+      stereo_width.process((1., 0.), 0., (false, 200.)),
+      (0.5, 0.5)
+    );
+    assert_eq!(stereo_width.process((1., 0.), 1., (false, 200.)), (1., 0.));
+    assert_eq!(
+      stereo_width.process((1., 0.), 2., (false, 200.)),
+      (1.5, -0.5)
+    );
+  }
+
+  #[test]
+  fn should_remove_low_side_content_when_mono_bass_is_enabled() {
+    assert!((get_side_gain(40., 1., false) - 1.).abs() < 0.01);
+    assert!(get_side_gain(40., 1., true) < 0.25);
+    assert!(get_side_gain(5000., 1., true) > 0.95);
+  }
+}
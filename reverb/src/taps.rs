@@ -4,27 +4,65 @@ mod dc_block;
 mod delay_read;
 mod early_reflections;
 mod grains;
+mod injection;
+mod lfo;
+mod multiband_decay;
 mod one_pole_filter;
 mod saturation;
 mod shimmer;
 
-pub use early_reflections::EarlyReflections;
+pub use early_reflections::{
+  EarlyReflections, Reflection, ReflectionPattern, RoomGeometry, RoomReflections,
+};
 use {
   crate::shared::{
     constants::{MAX_DEPTH, MAX_SIZE},
     delay_line::DelayLine,
     phasor::Phasor,
+    quality::Quality,
   },
   allpass_filter::AllpassFilter,
   average::Average,
   dc_block::DcBlock,
   delay_read::DelayRead,
   grains::Grains,
+  injection::Injection,
+  lfo::Lfo,
+  multiband_decay::MultibandDecay,
   one_pole_filter::OnePoleFilter,
   saturation::Saturation,
   shimmer::Shimmer,
   std::simd::{f32x4, num::SimdFloat},
 };
+pub use {
+  grains::{GrainParams, GrainWindow},
+  lfo::LfoShape,
+  shimmer::ShimmerMode,
+};
+
+const GOLDEN_RATIO: f32 = 1.618034;
+
+/// The per-sample values `Taps::process` reads, after smoothing and mapping.
+#[derive(Clone, Copy)]
+pub struct TapsParams {
+  pub size: f32,
+  pub speed: f32,
+  pub depth: f32,
+  pub lfo: (LfoShape, f32, f32, u64),
+  pub grains: GrainParams,
+  pub diffuse: f32,
+  pub absorb: f32,
+  pub decay: f32,
+  pub decay_multipliers: (f32, f32, f32),
+  pub crossovers: (f32, f32),
+  pub shimmer: f32,
+  pub shimmer_voices: (f32, f32, f32, f32),
+  pub shimmer_mode: ShimmerMode,
+  pub injection: (f32, f32, f32),
+  pub quality: Quality,
+  pub early: ReflectionPattern,
+  pub early_level: f32,
+}
 
 pub struct Taps {
   early_reflections: EarlyReflections,
@@ -32,13 +70,21 @@ pub struct Taps {
   time_fractions: [f32; 4],
   diffuser_times: [f32; 4],
   lfo_phase_offsets: [f32; 4],
+  lfos: [Lfo; 4],
   grains: [Grains; 4],
   dc_block: DcBlock,
   absorbance: OnePoleFilter,
   diffusers: [AllpassFilter; 4],
-  lfo_phasor: Phasor,
+  multiband_decay: MultibandDecay,
+  lfo_phasors: [Phasor; 4],
+  lfo_seed: u64,
+  lfo_detunes: [f32; 4],
+  lfo_rate_spread: f32,
+  lfo_rate_multipliers: [f32; 4],
+  is_lfo_rate_dirty: bool,
   average: Average,
   shimmer: Shimmer,
+  injection: Injection,
 }
 
 impl Taps {
@@ -58,7 +104,7 @@ impl Taps {
     ];
 
     Self {
-      early_reflections: EarlyReflections::new(),
+      early_reflections: EarlyReflections::new(sample_rate),
       delay_lines: time_fractions.map(|t| {
         DelayLine::new(
           (sample_rate * (MAX_SIZE * 0.001 * t + MAX_DEPTH)) as usize,
@@ -72,7 +118,12 @@ impl Taps {
         13.083333333333332,
         14.916666666666666,
       ],
-      grains: [Grains::new(); 4],
+      grains: [
+        Grains::new(sample_rate, 5),
+        Grains::new(sample_rate, 6),
+        Grains::new(sample_rate, 7),
+        Grains::new(sample_rate, 8),
+      ],
       dc_block: DcBlock::new(sample_rate),
       absorbance: OnePoleFilter::new(sample_rate),
       diffusers: [
@@ -81,88 +132,196 @@ impl Taps {
         AllpassFilter::new(sample_rate),
         AllpassFilter::new(sample_rate),
       ],
+      multiband_decay: MultibandDecay::new(sample_rate, time_fractions),
       lfo_phase_offsets: [0., 0.25, 0.5, 0.75],
-      lfo_phasor: Phasor::new(sample_rate),
+      lfos: [Lfo::new(1), Lfo::new(2), Lfo::new(3), Lfo::new(4)],
+      lfo_phasors: [
+        Phasor::new(sample_rate),
+        Phasor::new(sample_rate),
+        Phasor::new(sample_rate),
+        Phasor::new(sample_rate),
+      ],
+      lfo_seed: 0,
+      lfo_detunes: Self::get_lfo_detunes(0),
+      lfo_rate_spread: 0.,
+      lfo_rate_multipliers: [1.; 4],
+      is_lfo_rate_dirty: false,
       shimmer: Shimmer::new(sample_rate),
+      injection: Injection::new(),
       average: Average::new(sample_rate, 20.),
     }
   }
 
-  pub fn process(
-    &mut self,
-    input: (f32, f32),
-    size: f32,
-    speed: f32,
-    depth: f32,
-    diffuse: f32,
-    absorb: f32,
-    decay: f32,
-    shimmer: f32,
-  ) -> (f32, f32) {
-    let early_reflections = self.early_reflections.process(size, &self.delay_lines);
+  pub fn reset(&mut self) {
+    self.early_reflections.reset();
+    self.delay_lines.iter_mut().for_each(DelayLine::reset);
+    self.dc_block.reset();
+    self.absorbance.reset();
+    self.diffusers.iter_mut().for_each(AllpassFilter::reset);
+    self.multiband_decay.reset();
+    self.lfo_phasors.iter_mut().for_each(Phasor::reset);
+    self.average.reset();
+    self.shimmer.reset();
+    self.reset_modulation();
+  }
 
-    let delay_network_taps = self.read_from_delay_network(size, speed, depth);
+  pub fn set_room_reflections(&mut self, room_reflections: RoomReflections) {
+    self
+      .early_reflections
+      .set_room_reflections(room_reflections);
+  }
+
+  /// Returns the level the saturation stage tracks inside the tank and the gain compensation applied to it.
+  pub fn get_tank_levels(&self) -> (f32, f32) {
+    let average = self.average.get();
+    (average, Self::retrieve_gain_compensation(average, 0.4))
+  }
+
+  pub fn process(&mut self, input: (f32, f32), params: &TapsParams) -> ((f32, f32), (f32, f32)) {
+    let TapsParams {
+      size,
+      diffuse,
+      absorb,
+      decay,
+      decay_multipliers,
+      crossovers,
+      shimmer,
+      shimmer_voices,
+      shimmer_mode,
+      injection,
+      quality,
+      early,
+      early_level,
+      ..
+    } = *params;
+    let early_reflections =
+      self
+        .early_reflections
+        .process(size, &self.delay_lines, early, early_level, quality);
+
+    let delay_network_taps = self.read_from_delay_network(params);
     let average = self.average.get();
     let saturation_output = Saturation::process(delay_network_taps, average);
     let delay_network_output = Self::retrieve_delay_network_output(saturation_output);
     self.average.set(delay_network_taps.abs().reduce_max());
 
     let matrix_output = Self::apply_matrix(saturation_output);
-    let shimmer_output = self.shimmer.process(input, delay_network_output, shimmer);
+    let shimmer_output = self.shimmer.process(
+      input,
+      delay_network_output,
+      shimmer,
+      shimmer_voices,
+      shimmer_mode,
+      quality,
+    );
     let dc_block_output = self.dc_block.process(matrix_output);
     let absorb_output = self.absorbance.process(
-      dc_block_output + f32x4::from_array([shimmer_output.0, shimmer_output.1, 0., 0.]),
+      dc_block_output + self.injection.process(shimmer_output, injection),
       absorb,
     );
-    self.diffuse_and_write(absorb_output, diffuse, decay);
+    self.diffuse_and_write(
+      absorb_output,
+      diffuse,
+      decay,
+      decay_multipliers,
+      crossovers,
+      quality,
+    );
 
-    Self::mix_delay_network_and_reflections(
-      delay_network_output,
-      early_reflections,
-      Self::retrieve_gain_compensation(average, 0.4),
+    let gain_compensation = Self::retrieve_gain_compensation(average, 0.4);
+    (
+      Self::apply_gain_compensation(early_reflections, gain_compensation),
+      Self::apply_gain_compensation(delay_network_output, gain_compensation),
     )
   }
 
-  fn read_from_delay_network(&mut self, size: f32, speed: f32, depth: f32) -> f32x4 {
-    let phase = self.lfo_phasor.process(speed);
+  fn read_from_delay_network(&mut self, params: &TapsParams) -> f32x4 {
+    let (_, lfo_phase_spread, lfo_rate_spread, lfo_seed) = params.lfo;
+    self.set_seed(lfo_seed);
+    let phases = self.get_lfo_phases(params.speed, lfo_rate_spread);
 
     [
       self.delay_lines[0].delay_network_read(
-        size,
         self.time_fractions[0],
-        phase,
-        self.lfo_phase_offsets[0],
-        depth,
+        phases[0] + self.lfo_phase_offsets[0] * lfo_phase_spread,
+        &mut self.lfos[0],
         &mut self.grains[0],
+        params,
       ),
       self.delay_lines[1].delay_network_read(
-        size,
         self.time_fractions[1],
-        phase,
-        self.lfo_phase_offsets[1],
-        depth,
+        phases[1] + self.lfo_phase_offsets[1] * lfo_phase_spread,
+        &mut self.lfos[1],
         &mut self.grains[1],
+        params,
       ),
       self.delay_lines[2].delay_network_read(
-        size,
         self.time_fractions[2],
-        phase,
-        self.lfo_phase_offsets[2],
-        depth,
+        phases[2] + self.lfo_phase_offsets[2] * lfo_phase_spread,
+        &mut self.lfos[2],
         &mut self.grains[2],
+        params,
       ),
       self.delay_lines[3].delay_network_read(
-        size,
         self.time_fractions[3],
-        phase,
-        self.lfo_phase_offsets[3],
-        depth,
+        phases[3] + self.lfo_phase_offsets[3] * lfo_phase_spread,
+        &mut self.lfos[3],
         &mut self.grains[3],
+        params,
       ),
     ]
     .into()
   }
 
+  /// Reseeds every random source of the modulation, so renders with the same seed are reproducible.
+  fn set_seed(&mut self, seed: u64) {
+    if seed != self.lfo_seed {
+      self.lfo_seed = seed;
+      self.lfo_detunes = Self::get_lfo_detunes(seed);
+      self.is_lfo_rate_dirty = true;
+      self.reset_modulation();
+    }
+  }
+
+  /// Restarts the random state of the lfos and grains from the current seed.
+  fn reset_modulation(&mut self) {
+    for (i, (lfo, grains)) in self.lfos.iter_mut().zip(&mut self.grains).enumerate() {
+      let line_seed = self.lfo_seed * 8 + i as u64;
+      lfo.reset(line_seed + 1);
+      grains.reset(line_seed + 5);
+    }
+  }
+
+  /// Advances a phasor per delay line. With a rate spread above zero every line runs at its own rate around
+  /// `speed`, up to an octave away, so the lines never line up again and the modulation decorrelates.
+  fn get_lfo_phases(&mut self, speed: f32, rate_spread: f32) -> [f32; 4] {
+    if self.is_lfo_rate_dirty || rate_spread != self.lfo_rate_spread {
+      self.is_lfo_rate_dirty = false;
+      self.lfo_rate_spread = rate_spread;
+      self.lfo_rate_multipliers = self.lfo_detunes.map(|detune| (detune * rate_spread).exp2());
+    }
+
+    [0, 1, 2, 3].map(|i| self.lfo_phasors[i].process(speed * self.lfo_rate_multipliers[i]))
+  }
+
+  /// Spaces the detune of each line by the golden ratio, which keeps the rate ratios irrational. The seed picks
+  /// where the sequence starts. The detunes are centred on zero, so the rates spread around `speed`, and scaled
+  /// so the line furthest away is detuned by an octave.
+  fn get_lfo_detunes(seed: u64) -> [f32; 4] {
+    let offset = if seed == 0 {
+      0.
+    } else {
+      fastrand::Rng::with_seed(seed).f32()
+    };
+    let detunes = [0., 1., 2., 3.].map(|i: f32| (offset + i * GOLDEN_RATIO).fract());
+    let mean = detunes.iter().sum::<f32>() / 4.;
+    let detunes = detunes.map(|detune| detune - mean);
+    let max_detune = detunes
+      .iter()
+      .fold(0., |max: f32, detune| max.max(detune.abs()));
+    detunes.map(|detune| detune / max_detune)
+  }
+
   fn apply_matrix(input: f32x4) -> f32x4 {
     f32x4::from_array([
       (Self::MATRIX[0] * input).reduce_sum(),
@@ -172,26 +331,40 @@ impl Taps {
     ])
   }
 
-  fn diffuse_and_write(&mut self, input: f32x4, diffuse: f32, decay: f32) {
-    input.to_array().into_iter().enumerate().for_each(|(i, x)| {
-      let diffuse_output = self.diffusers[i].process(x, self.diffuser_times[i], diffuse);
-
-      self.delay_lines[i].write(diffuse_output * decay);
+  fn diffuse_and_write(
+    &mut self,
+    input: f32x4,
+    diffuse: f32,
+    decay: f32,
+    decay_multipliers: (f32, f32, f32),
+    crossovers: (f32, f32),
+    quality: Quality,
+  ) {
+    let interp = quality.fixed_interpolation();
+    let mut diffuse_output = input.to_array();
+    diffuse_output.iter_mut().enumerate().for_each(|(i, x)| {
+      *x = self.diffusers[i].process(*x, self.diffuser_times[i], diffuse, interp);
     });
+
+    let decay_output = self.multiband_decay.process(
+      f32x4::from_array(diffuse_output),
+      decay,
+      decay_multipliers,
+      crossovers,
+    );
+    decay_output
+      .to_array()
+      .into_iter()
+      .enumerate()
+      .for_each(|(i, x)| self.delay_lines[i].write(x));
   }
 
   fn retrieve_delay_network_output(inputs: f32x4) -> (f32, f32) {
     ((inputs[0] + inputs[2]) * 0.5, (inputs[1] + inputs[3]) * 0.5)
   }
 
-  fn mix_delay_network_and_reflections(
-    (left_delay_network_out, right_delay_network_out): (f32, f32),
-    early_reflections: (f32, f32),
-    gain_compensation: f32,
-  ) -> (f32, f32) {
-    let left_out = left_delay_network_out + early_reflections.0;
-    let right_out = right_delay_network_out + early_reflections.1;
-    (left_out * gain_compensation, right_out * gain_compensation)
+  fn apply_gain_compensation(input: (f32, f32), gain_compensation: f32) -> (f32, f32) {
+    (input.0 * gain_compensation, input.1 * gain_compensation)
   }
 
   fn retrieve_gain_compensation(average: f32, threshold: f32) -> f32 {
@@ -202,3 +375,45 @@ impl Taps {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::Taps;
+
+  fn get_phases(rate_spread: f32, seed: u64) -> [f32; 4] {
+    let mut taps = Taps::new(1000.);
+    taps.set_seed(seed);
+    (0..999).fold([0.; 4], |_, _| taps.get_lfo_phases(1., rate_spread))
+  }
+
+  #[test]
+  fn should_share_the_lfo_rate_without_rate_spread() {
+    let phases = get_phases(0., 3);
+    assert!(phases
+      .iter()
+      .all(|phase| (phase - phases[0]).abs() < 0.0001));
+  }
+
+  #[test]
+  fn should_detune_lfo_rates_deterministically() {
+    let phases = get_phases(0.5, 3);
+    for i in 1..4 {
+      assert!((phases[i] - phases[0]).abs() > 0.01);
+    }
+    assert_eq!(phases, get_phases(0.5, 3));
+    assert_ne!(phases, get_phases(0.5, 4));
+  }
+
+  #[test]
+  fn should_spread_lfo_rates_around_speed() {
+    for seed in 0..16 {
+      let detunes = Taps::get_lfo_detunes(seed);
+      let mean = detunes.iter().sum::<f32>() / 4.;
+      let max_detune = detunes
+        .iter()
+        .fold(0., |max: f32, detune| max.max(detune.abs()));
+      assert!(mean.abs() < 0.0001);
+      assert!((max_detune - 1.).abs() < 0.0001);
+    }
+  }
+}
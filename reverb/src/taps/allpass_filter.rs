@@ -12,8 +12,12 @@ impl AllpassFilter {
     }
   }
 
-  pub fn process(&mut self, input: f32, time: f32, gain: f32) -> f32 {
-    let read_output = self.delay_line.read(time, Interpolation::Linear);
+  pub fn reset(&mut self) {
+    self.delay_line.reset();
+  }
+
+  pub fn process(&mut self, input: f32, time: f32, gain: f32, interp: Interpolation) -> f32 {
+    let read_output = self.delay_line.read(time, interp);
     let feedback = read_output * gain;
     let allpass_input = input + feedback;
     let feedforward = allpass_input * -gain;
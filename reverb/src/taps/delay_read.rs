@@ -1,88 +1,78 @@
-use std::f32::consts::TAU;
+use crate::shared::delay_line::DelayLine;
 
-use crate::shared::{
-  delay_line::{DelayLine, Interpolation},
-  float_ext::FloatExt,
-};
-
-use super::grains::Grains;
+use super::{grains::Grains, lfo::Lfo, TapsParams};
 
 pub trait DelayRead {
   fn delay_network_read(
     &self,
-    size: f32,
     time_fraction: f32,
     lfo_phase: f32,
-    lfo_phase_offset: f32,
-    lfo_depth: f32,
+    lfo: &mut Lfo,
     grains: &mut Grains,
+    params: &TapsParams,
   ) -> f32;
 
   fn vibrato_read(
     &self,
-    size: f32,
-    lfo_phase: f32,
-    lfo_phase_offset: f32,
-    lfo_depth: f32,
-    time_fraction: f32,
-  ) -> f32;
-
-  fn grain_read(
-    &self,
-    grains: &mut Grains,
-    size: f32,
     time_fraction: f32,
     lfo_phase: f32,
-    lfo_depth: f32,
+    lfo: &mut Lfo,
+    params: &TapsParams,
   ) -> f32;
+
+  fn grain_read(&self, grains: &mut Grains, time_fraction: f32, params: &TapsParams) -> f32;
 }
 
 impl DelayRead for DelayLine {
   fn delay_network_read(
     &self,
-    size: f32,
     time_fraction: f32,
     lfo_phase: f32,
-    lfo_phase_offset: f32,
-    lfo_depth: f32,
+    lfo: &mut Lfo,
     grains: &mut Grains,
+    params: &TapsParams,
   ) -> f32 {
-    if lfo_depth == 0. {
-      self.read(size * time_fraction, Interpolation::Linear)
-    } else if lfo_depth < 0. {
-      self.vibrato_read(size, lfo_phase, lfo_phase_offset, lfo_depth, time_fraction)
+    if params.depth == 0. {
+      self.read(
+        params.size * time_fraction,
+        params.quality.static_interpolation(),
+      )
+    } else if params.depth < 0. {
+      self.vibrato_read(time_fraction, lfo_phase, lfo, params)
     } else {
-      self.grain_read(grains, size, time_fraction, lfo_phase, lfo_depth)
+      self.grain_read(grains, time_fraction, params)
     }
   }
 
   fn vibrato_read(
     &self,
-    size: f32,
-    lfo_phase: f32,
-    lfo_phase_offset: f32,
-    lfo_depth: f32,
     time_fraction: f32,
+    lfo_phase: f32,
+    lfo: &mut Lfo,
+    params: &TapsParams,
   ) -> f32 {
-    let lfo_phase_input = lfo_phase + lfo_phase_offset;
-    let phase = if lfo_phase_input > 1. {
-      lfo_phase_input - 1.
+    // The phase offset is added by the caller, so the phase can exceed one once
+    let phase = if lfo_phase >= 1. {
+      lfo_phase - 1.
     } else {
-      lfo_phase_input
-    } * TAU;
-    let lfo = phase.fast_sin() * lfo_depth.abs();
+      lfo_phase
+    };
+    let lfo = lfo.process(phase, params.lfo.0) * params.depth.abs();
 
-    self.read(time_fraction * size + lfo, Interpolation::Linear)
+    self.read(
+      time_fraction * params.size + lfo,
+      params.quality.modulated_interpolation(),
+    )
   }
 
-  fn grain_read(
-    &self,
-    grains: &mut Grains,
-    size: f32,
-    time_fraction: f32,
-    lfo_phase: f32,
-    lfo_depth: f32,
-  ) -> f32 {
-    grains.process(self, size, time_fraction, lfo_phase, lfo_depth)
+  fn grain_read(&self, grains: &mut Grains, time_fraction: f32, params: &TapsParams) -> f32 {
+    grains.process(
+      self,
+      params.size,
+      time_fraction,
+      params.grains,
+      params.depth,
+      params.quality,
+    )
   }
 }
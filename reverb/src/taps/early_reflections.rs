@@ -1,59 +1,404 @@
+mod image_source;
+pub use image_source::{Reflection, RoomGeometry, RoomReflections};
+
 use crate::shared::{
   constants::{MAX_SIZE, MIN_SIZE},
   delay_line::{DelayLine, Interpolation},
   float_ext::FloatExt,
+  quality::Quality,
 };
 
-const LAST_REFLECTION_GAIN_IN_DB: f32 = -6.;
 const MINUS_NINE_DB: f32 = 0.707946;
 const MINUS_TWENTY_ONE_DB: f32 = 0.089125;
+const REFERENCE_REFLECTION_COUNT: f32 = 6.;
+// The time in milliseconds it takes to fade from one set of room reflections to the next
+const ROOM_REFLECTIONS_CROSSFADE_TIME: f32 = 50.;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ReflectionPattern {
+  Room,
+  Hall,
+  Plate,
+  Chamber,
+  Geometry,
+  None,
+}
+
+impl From<i32> for ReflectionPattern {
+  fn from(value: i32) -> Self {
+    match value {
+      1 => ReflectionPattern::Hall,
+      2 => ReflectionPattern::Plate,
+      3 => ReflectionPattern::Chamber,
+      4 => ReflectionPattern::Geometry,
+      5 => ReflectionPattern::None,
+      _ => ReflectionPattern::Room,
+    }
+  }
+}
+
+struct Pattern {
+  // Each reflection is a pair of a delay line index and a time factor that's multiplied by size.
+  reflections: [&'static [(usize, f32)]; 2],
+  last_reflection_gain_in_db: f32,
+  curve: f32,
+}
+
+const ROOM: Pattern = Pattern {
+  reflections: [
+    &[
+      (0, 0.),
+      (0, 0.188),
+      (0, 0.278),
+      (0, 0.38),
+      (0, 0.482),
+      (0, 0.584),
+    ],
+    &[
+      (1, 0.018),
+      (1, 0.086),
+      (1, 0.29),
+      (1, 0.392),
+      (1, 0.494),
+      (1, 0.597),
+    ],
+  ],
+  last_reflection_gain_in_db: -6.,
+  curve: 1.,
+};
+
+const HALL: Pattern = Pattern {
+  reflections: [
+    &[
+      (0, 0.012),
+      (2, 0.097),
+      (0, 0.171),
+      (2, 0.263),
+      (0, 0.329),
+      (2, 0.418),
+      (2, 0.541),
+      (2, 0.677),
+    ],
+    &[
+      (1, 0.031),
+      (3, 0.119),
+      (1, 0.204),
+      (3, 0.297),
+      (1, 0.372),
+      (3, 0.466),
+      (3, 0.598),
+      (3, 0.712),
+    ],
+  ],
+  last_reflection_gain_in_db: -12.,
+  curve: 0.8,
+};
+
+const PLATE: Pattern = Pattern {
+  reflections: [
+    &[
+      (0, 0.004),
+      (2, 0.023),
+      (0, 0.041),
+      (2, 0.067),
+      (0, 0.089),
+      (2, 0.118),
+      (0, 0.146),
+      (2, 0.179),
+      (0, 0.213),
+      (2, 0.251),
+    ],
+    &[
+      (1, 0.009),
+      (3, 0.031),
+      (1, 0.052),
+      (3, 0.074),
+      (1, 0.101),
+      (3, 0.127),
+      (1, 0.158),
+      (3, 0.191),
+      (1, 0.226),
+      (3, 0.268),
+    ],
+  ],
+  last_reflection_gain_in_db: -4.5,
+  curve: 1.5,
+};
+
+const CHAMBER: Pattern = Pattern {
+  reflections: [
+    &[
+      (0, 0.008),
+      (2, 0.071),
+      (0, 0.149),
+      (2, 0.226),
+      (0, 0.316),
+      (2, 0.402),
+      (2, 0.517),
+    ],
+    &[
+      (1, 0.024),
+      (3, 0.093),
+      (1, 0.171),
+      (3, 0.248),
+      (1, 0.339),
+      (3, 0.437),
+      (3, 0.553),
+    ],
+  ],
+  last_reflection_gain_in_db: -9.,
+  curve: 1.,
+};
 
 pub struct EarlyReflections {
-  reflections: [[f32; 6]; 2],
-  attenuations: [f32; 6],
+  // Each reflection is stored as a delay line index, a time factor and an attenuation.
+  patterns: [[Vec<(usize, f32, f32)>; 2]; 4],
+  room_reflections: RoomReflections,
+  previous_room_reflections: RoomReflections,
+  next_room_reflections: Option<RoomReflections>,
+  crossfade: f32,
+  crossfade_step: f32,
+  is_initialized: bool,
 }
 
 impl EarlyReflections {
   const SIZE_MULTIPLIER: f32 = (-MINUS_NINE_DB + MINUS_TWENTY_ONE_DB) / MAX_SIZE;
 
-  pub fn new() -> Self {
-    let reflections = [
-      [0., 0.188, 0.278, 0.38, 0.482, 0.584],
-      [0.018, 0.086, 0.29, 0.392, 0.494, 0.597],
-    ];
-    debug_assert!(reflections[0].len() == reflections[1].len());
-    let length = reflections[0].len();
-
+  pub fn new(sample_rate: f32) -> Self {
     Self {
-      reflections,
-      attenuations: (0..length)
-        .map(|index| (index as f32 / length as f32 * LAST_REFLECTION_GAIN_IN_DB).dbtoa())
-        .collect::<Vec<f32>>()
-        .try_into()
-        .unwrap(),
+      patterns: [ROOM, HALL, PLATE, CHAMBER].map(|pattern| Self::create_reflections(&pattern)),
+      room_reflections: RoomReflections::default(),
+      previous_room_reflections: RoomReflections::default(),
+      next_room_reflections: None,
+      crossfade: 1.,
+      crossfade_step: 1000. / (sample_rate * ROOM_REFLECTIONS_CROSSFADE_TIME),
+      is_initialized: false,
     }
   }
 
-  pub fn process(&self, size: f32, taps: &[DelayLine; 4]) -> (f32, f32) {
-    let gain = (size - MIN_SIZE) * Self::SIZE_MULTIPLIER + MINUS_NINE_DB;
+  /// Skips any fade in progress, so the tail starts from the latest room reflections.
+  pub fn reset(&mut self) {
+    if let Some(room_reflections) = self.next_room_reflections.take() {
+      self.room_reflections = room_reflections;
+    }
+    self.crossfade = 1.;
+  }
+
+  /// The first room reflections are applied right away. After that each new set fades in over
+  /// ROOM_REFLECTIONS_CROSSFADE_TIME, and a set that arrives during a fade waits until the fade is done.
+  pub fn set_room_reflections(&mut self, room_reflections: RoomReflections) {
+    if !self.is_initialized {
+      self.room_reflections = room_reflections;
+      self.is_initialized = true;
+    } else if self.crossfade < 1. {
+      self.next_room_reflections = Some(room_reflections);
+    } else {
+      self.start_crossfade(room_reflections);
+    }
+  }
+
+  pub fn process(
+    &mut self,
+    size: f32,
+    taps: &[DelayLine; 4],
+    pattern: ReflectionPattern,
+    level: f32,
+    quality: Quality,
+  ) -> (f32, f32) {
+    self.update_crossfade();
+    let reflections = match pattern {
+      ReflectionPattern::Room => &self.patterns[0],
+      ReflectionPattern::Hall => &self.patterns[1],
+      ReflectionPattern::Plate => &self.patterns[2],
+      ReflectionPattern::Chamber => &self.patterns[3],
+      ReflectionPattern::Geometry => {
+        return self.process_room_reflections(taps, level, quality);
+      }
+      ReflectionPattern::None => return (0., 0.),
+    };
+    if level == 0. {
+      return (0., 0.);
+    }
+
+    let gain = ((size - MIN_SIZE) * Self::SIZE_MULTIPLIER + MINUS_NINE_DB) * level;
+    let interp = quality.static_interpolation();
 
     (
-      self.reflections[0]
-        .into_iter()
-        .zip(self.attenuations)
-        .map(|(time_factor, attenuation)| {
-          taps[0].read(size * time_factor, Interpolation::Linear) * attenuation
+      reflections[0]
+        .iter()
+        .map(|(index, time_factor, attenuation)| {
+          taps[*index].read(size * time_factor, interp) * attenuation
         })
         .sum::<f32>()
         * gain,
-      self.reflections[1]
-        .into_iter()
-        .zip(self.attenuations)
-        .map(|(time_factor, attenuation)| {
-          taps[1].read(size * time_factor, Interpolation::Linear) * attenuation
+      reflections[1]
+        .iter()
+        .map(|(index, time_factor, attenuation)| {
+          taps[*index].read(size * time_factor, interp) * attenuation
         })
         .sum::<f32>()
         * gain,
     )
   }
+
+  fn process_room_reflections(
+    &self,
+    taps: &[DelayLine; 4],
+    level: f32,
+    quality: Quality,
+  ) -> (f32, f32) {
+    if level == 0. {
+      return (0., 0.);
+    }
+    let interp = quality.fixed_interpolation();
+
+    let (left, right) = Self::read_room_reflections(&self.room_reflections, taps, interp);
+    if self.crossfade < 1. {
+      let (previous_left, previous_right) =
+        Self::read_room_reflections(&self.previous_room_reflections, taps, interp);
+      (
+        previous_left.mix(left, self.crossfade) * level,
+        previous_right.mix(right, self.crossfade) * level,
+      )
+    } else {
+      (left * level, right * level)
+    }
+  }
+
+  fn read_room_reflections(
+    room_reflections: &RoomReflections,
+    taps: &[DelayLine; 4],
+    interp: Interpolation,
+  ) -> (f32, f32) {
+    room_reflections
+      .get()
+      .iter()
+      .enumerate()
+      .fold((0., 0.), |(left, right), (i, reflection)| {
+        let tap = taps[i % 4].read(reflection.time, interp);
+        (
+          left + tap * reflection.gain.0,
+          right + tap * reflection.gain.1,
+        )
+      })
+  }
+
+  fn start_crossfade(&mut self, room_reflections: RoomReflections) {
+    self.previous_room_reflections = self.room_reflections;
+    self.room_reflections = room_reflections;
+    self.crossfade = 0.;
+  }
+
+  fn update_crossfade(&mut self) {
+    if self.crossfade < 1. {
+      self.crossfade = (self.crossfade + self.crossfade_step).min(1.);
+    } else if let Some(room_reflections) = self.next_room_reflections.take() {
+      self.start_crossfade(room_reflections);
+    }
+  }
+
+  fn create_reflections(pattern: &Pattern) -> [Vec<(usize, f32, f32)>; 2] {
+    debug_assert!(pattern.reflections[0].len() == pattern.reflections[1].len());
+    let length = pattern.reflections[0].len() as f32;
+    // Keeps patterns with more reflections about as loud as patterns with fewer reflections
+    let normalization = (REFERENCE_REFLECTION_COUNT / length).sqrt();
+
+    pattern.reflections.map(|reflections| {
+      reflections
+        .iter()
+        .enumerate()
+        .map(|(i, (index, time_factor))| {
+          let attenuation =
+            ((i as f32 / length).powf(pattern.curve) * pattern.last_reflection_gain_in_db).dbtoa()
+              * normalization;
+          (*index, *time_factor, attenuation)
+        })
+        .collect()
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{EarlyReflections, ReflectionPattern, RoomGeometry};
+  use crate::shared::{delay_line::DelayLine, quality::Quality};
+
+  #[test]
+  fn should_be_silent_when_disabled() {
+    let sample_rate = 44100.;
+    let mut early_reflections = EarlyReflections::new(sample_rate);
+    let mut delay_lines = [(); 4].map(|_| DelayLine::new(sample_rate as usize, sample_rate));
+    delay_lines
+      .iter_mut()
+      .for_each(|delay_line| (0..4410).for_each(|_| delay_line.write(1.)));
+
+    let room = early_reflections.process(
+      80.,
+      &delay_lines,
+      ReflectionPattern::Room,
+      1.,
+      Quality::Standard,
+    );
+    let none = early_reflections.process(
+      80.,
+      &delay_lines,
+      ReflectionPattern::None,
+      1.,
+      Quality::Standard,
+    );
+    let muted = early_reflections.process(
+      80.,
+      &delay_lines,
+      ReflectionPattern::Hall,
+      0.,
+      Quality::Standard,
+    );
+    assert!(room.0 > 0. && room.1 > 0.);
+    assert_eq!(none, (0., 0.));
+    assert_eq!(muted, (0., 0.));
+  }
+
+  #[test]
+  fn should_crossfade_to_new_room_reflections() {
+    let sample_rate = 44100.;
+    let mut early_reflections = EarlyReflections::new(sample_rate);
+    let mut settled_early_reflections = EarlyReflections::new(sample_rate);
+    let mut delay_lines = [(); 4].map(|_| DelayLine::new(sample_rate as usize, sample_rate));
+    delay_lines
+      .iter_mut()
+      .for_each(|delay_line| (0..sample_rate as usize).for_each(|_| delay_line.write(1.)));
+    let small_room = RoomGeometry {
+      dimensions: (4., 5., 3.),
+      source: (0.5, 0.3, 0.5),
+      listener: (0.4, 0.7, 0.5),
+      absorption: 0.2,
+    };
+    let large_room = RoomGeometry {
+      dimensions: (20., 30., 10.),
+      ..small_room
+    };
+    let process = |early_reflections: &mut EarlyReflections| {
+      early_reflections.process(
+        80.,
+        &delay_lines,
+        ReflectionPattern::Geometry,
+        1.,
+        Quality::Standard,
+      )
+    };
+
+    early_reflections.set_room_reflections(small_room.compute_reflections());
+    settled_early_reflections.set_room_reflections(large_room.compute_reflections());
+    let small = process(&mut early_reflections);
+    let large = process(&mut settled_early_reflections);
+    assert!((large.0 - small.0).abs() > 0.1);
+
+    early_reflections.set_room_reflections(large_room.compute_reflections());
+    let first = process(&mut early_reflections);
+    assert!((first.0 - small.0).abs() < (large.0 - small.0).abs() * 0.01);
+    (0..sample_rate as usize / 10).for_each(|_| {
+      process(&mut early_reflections);
+    });
+    assert_eq!(process(&mut early_reflections), large);
+  }
 }
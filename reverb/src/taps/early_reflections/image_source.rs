@@ -0,0 +1,142 @@
+use std::f32::consts::FRAC_PI_4;
+
+const SPEED_OF_SOUND: f32 = 343.;
+const MAX_ORDER: i32 = 2;
+const MAX_REFLECTION_TIME: f32 = 300.;
+// The number of image sources with a reflection order between one and MAX_ORDER
+pub const MAX_REFLECTIONS: usize = 24;
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct RoomGeometry {
+  /// The room dimensions in metres as (width, depth, height)
+  pub dimensions: (f32, f32, f32),
+  /// The source position relative to the room dimensions, each axis ranges from 0 to 1
+  pub source: (f32, f32, f32),
+  /// The listener position relative to the room dimensions, each axis ranges from 0 to 1
+  pub listener: (f32, f32, f32),
+  /// The fraction of energy the walls absorb on each reflection
+  pub absorption: f32,
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct Reflection {
+  /// The delay relative to the direct sound in milliseconds
+  pub time: f32,
+  pub gain: (f32, f32),
+}
+
+#[derive(Clone, Copy, Default)]
+pub struct RoomReflections {
+  reflections: [Reflection; MAX_REFLECTIONS],
+  length: usize,
+}
+
+impl RoomReflections {
+  pub fn get(&self) -> &[Reflection] {
+    &self.reflections[..self.length]
+  }
+
+  fn push(&mut self, reflection: Reflection) {
+    if self.length < MAX_REFLECTIONS {
+      self.reflections[self.length] = reflection;
+      self.length += 1;
+    }
+  }
+}
+
+impl RoomGeometry {
+  /// Computes the early reflections of a shoebox room with the image-source method.
+  /// This doesn't allocate, but it's meant to run off the audio thread whenever the geometry changes.
+  pub fn compute_reflections(&self) -> RoomReflections {
+    let (width, depth, height) = self.dimensions;
+    let source = (
+      self.source.0 * width,
+      self.source.1 * depth,
+      self.source.2 * height,
+    );
+    let listener = (
+      self.listener.0 * width,
+      self.listener.1 * depth,
+      self.listener.2 * height,
+    );
+    let direct_distance = Self::distance(source, listener).max(0.1);
+    let reflection_gain = (1. - self.absorption.clamp(0., 1.)).sqrt();
+    let mut room_reflections = RoomReflections::default();
+
+    for i in -MAX_ORDER..=MAX_ORDER {
+      for j in -MAX_ORDER..=MAX_ORDER {
+        for k in -MAX_ORDER..=MAX_ORDER {
+          let order = i.abs() + j.abs() + k.abs();
+          if order == 0 || order > MAX_ORDER {
+            continue;
+          }
+          let image = (
+            Self::mirror(i, source.0, width),
+            Self::mirror(j, source.1, depth),
+            Self::mirror(k, source.2, height),
+          );
+          let distance = Self::distance(image, listener).max(0.1);
+          let time = (distance - direct_distance) / SPEED_OF_SOUND * 1000.;
+          if time > MAX_REFLECTION_TIME {
+            continue;
+          }
+
+          let gain = reflection_gain.powi(order) * direct_distance / distance;
+          // The listener faces the depth axis, so the width axis determines the panning.
+          let pan = ((image.0 - listener.0) / distance).clamp(-1., 1.);
+          let angle = (pan + 1.) * FRAC_PI_4;
+
+          room_reflections.push(Reflection {
+            time,
+            gain: (gain * angle.cos(), gain * angle.sin()),
+          });
+        }
+      }
+    }
+
+    room_reflections
+  }
+
+  fn mirror(index: i32, position: f32, dimension: f32) -> f32 {
+    if index % 2 == 0 {
+      index as f32 * dimension + position
+    } else {
+      (index + 1) as f32 * dimension - position
+    }
+  }
+
+  fn distance(a: (f32, f32, f32), b: (f32, f32, f32)) -> f32 {
+    let x = a.0 - b.0;
+    let y = a.1 - b.1;
+    let z = a.2 - b.2;
+    (x * x + y * y + z * z).sqrt()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{RoomGeometry, MAX_REFLECTIONS};
+
+  #[test]
+  fn should_compute_reflections() {
+    let geometry = RoomGeometry {
+      dimensions: (8., 6., 3.),
+      source: (0.25, 0.5, 0.5),
+      listener: (0.75, 0.5, 0.5),
+      absorption: 0.3,
+    };
+    let reflections = geometry.compute_reflections();
+
+    assert_eq!(reflections.get().len(), MAX_REFLECTIONS);
+    reflections.get().iter().for_each(|reflection| {
+      assert!(reflection.time >= 0.);
+      assert!(reflection.gain.0 < 1.);
+      assert!(reflection.gain.1 < 1.);
+    });
+    // The reflection of the right wall arrives after 2 * 2 metres
+    assert!(reflections
+      .get()
+      .iter()
+      .any(|reflection| (reflection.time - 4. / 343. * 1000.).abs() < 0.01));
+  }
+}
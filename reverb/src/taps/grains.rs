@@ -1,8 +1,10 @@
 mod delta;
 use crate::shared::{
-  constants::MAX_DEPTH,
+  constants::{MAX_DEPTH, MAX_GRAIN_DENSITY},
   delay_line::{DelayLine, Interpolation},
   float_ext::FloatExt,
+  phasor::Phasor,
+  quality::Quality,
 };
 use delta::Delta;
 use std::f32::consts::PI;
@@ -10,34 +12,100 @@ use std::f32::consts::PI;
 const FADE_THRESHOLD_FACTOR: f32 = 0.05;
 const FADE_THRESHOLD: f32 = MAX_DEPTH * FADE_THRESHOLD_FACTOR;
 
-#[derive(Clone, Copy)]
+/// Grain size, density, window, position curve, pitch jitter in cents and reverse probability.
+pub type GrainParams = (f32, usize, GrainWindow, f32, f32, f32);
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum GrainWindow {
+  Hann,
+  Tukey,
+  Trapezoid,
+}
+
+impl GrainWindow {
+  fn process(self, phase: f32) -> f32 {
+    match self {
+      GrainWindow::Hann => {
+        let window = (phase * PI).fast_sin();
+        window * window
+      }
+      // Cosine tapers over the first and last quarter of the grain
+      GrainWindow::Tukey => {
+        let taper = phase.min(1. - phase) * 4.;
+        if taper < 1. {
+          let window = (taper * PI * 0.5).fast_sin();
+          window * window
+        } else {
+          1.
+        }
+      }
+      GrainWindow::Trapezoid => (phase.min(1. - phase) * 4.).min(1.),
+    }
+  }
+}
+
+impl From<i32> for GrainWindow {
+  fn from(value: i32) -> Self {
+    match value {
+      1 => GrainWindow::Tukey,
+      2 => GrainWindow::Trapezoid,
+      _ => GrainWindow::Hann,
+    }
+  }
+}
+
 pub struct Grains {
-  start_position: [f32; 2],
-  delta: [Delta; 2],
-  phase_offset: [f32; 2],
+  rng: fastrand::Rng,
+  phasor: Phasor,
+  start_position: [f32; MAX_GRAIN_DENSITY],
+  drift: [f32; MAX_GRAIN_DENSITY],
+  delta: [Delta; MAX_GRAIN_DENSITY],
 }
 
 impl Grains {
-  pub fn new() -> Self {
+  pub fn new(sample_rate: f32, seed: u64) -> Self {
     Self {
-      start_position: [0.; 2],
-      delta: [Delta::new(); 2],
-      phase_offset: [0., 0.5],
+      rng: fastrand::Rng::with_seed(seed),
+      phasor: Phasor::new(sample_rate),
+      start_position: [0.; MAX_GRAIN_DENSITY],
+      drift: [0.; MAX_GRAIN_DENSITY],
+      delta: [Delta::new(); MAX_GRAIN_DENSITY],
     }
   }
 
+  /// Restarts the grains from `seed`, so the same input renders the same start positions, pitches and
+  /// directions again.
+  pub fn reset(&mut self, seed: u64) {
+    self.rng.seed(seed);
+    self.phasor.reset();
+    self.start_position = [0.; MAX_GRAIN_DENSITY];
+    self.drift = [0.; MAX_GRAIN_DENSITY];
+    self.delta = [Delta::new(); MAX_GRAIN_DENSITY];
+  }
+
+  /// Reads `density` overlapping grains of `grain_size` milliseconds. Every grain starts at a random position
+  /// within `lfo_depth`, where `curve` skews the randomisation towards short (-1) or long (1) offsets. Each grain
+  /// is detuned by up to `pitch_jitter` cents and plays in reverse with a probability of `reverse`.
   pub fn process(
     &mut self,
     delay_line: &DelayLine,
     size: f32,
     time_fraction: f32,
-    lfo_phase: f32,
+    grain_params: GrainParams,
     lfo_depth: f32,
+    quality: Quality,
   ) -> f32 {
-    let grains_out = self.apply_grains(delay_line, size, time_fraction, lfo_phase, lfo_depth);
+    let grains_out = self.apply_grains(
+      delay_line,
+      size,
+      time_fraction,
+      grain_params,
+      lfo_depth,
+      quality.modulated_interpolation(),
+    );
     if lfo_depth < FADE_THRESHOLD {
       self.mix(
-        delay_line.read(size * time_fraction, Interpolation::Linear),
+        delay_line.read(size * time_fraction, quality.static_interpolation()),
         grains_out,
         lfo_depth,
         FADE_THRESHOLD_FACTOR,
@@ -57,22 +125,59 @@ impl Grains {
     delay_line: &DelayLine,
     size: f32,
     time_fraction: f32,
-    lfo_phase: f32,
+    (grain_size, density, window, curve, pitch_jitter, reverse): GrainParams,
     lfo_depth: f32,
+    interp: Interpolation,
   ) -> f32 {
-    (0..2)
-      .map(|i| {
-        let phase = Self::wrap(lfo_phase + self.phase_offset[i]);
-        let trigger = self.delta[i].process(phase) < 0.;
-        if trigger {
-          self.start_position[i] = fastrand::f32() * lfo_depth;
+    let lfo_phase = self.phasor.process(1000. / grain_size);
+    let phase_step = (density as f32).recip();
+
+    let (output, window_sum) = (0..density).fold((0., 0.), |(output, window_sum), i| {
+      let phase = Self::wrap(lfo_phase + i as f32 * phase_step);
+      let trigger = self.delta[i].process(phase) < 0.;
+      if trigger {
+        self.start_position[i] = self.rng.f32().powf((curve * -2.).exp2()) * lfo_depth;
+        let cents = if pitch_jitter > 0. {
+          (self.rng.f32() * 2. - 1.) * pitch_jitter
+        } else {
+          0.
         };
-        let window = (phase * PI).fast_sin();
-        let time = size * time_fraction + self.start_position[i];
+        let is_reversed = reverse > 0. && self.rng.f32() < reverse;
+        self.drift[i] = Self::get_drift(cents, is_reversed);
+      };
+      let window = window.process(phase);
+      let time = size * time_fraction
+        + self.start_position[i]
+        + Self::get_offset(self.drift[i], phase, grain_size);
 
-        delay_line.read(time, Interpolation::Linear) * window * window
-      })
-      .sum()
+      (
+        output + delay_line.read(time, interp) * window,
+        window_sum + window,
+      )
+    });
+    // Keeps the overlapping windows at unity gain for every window shape and density, so the feedback loop
+    // stays stable
+    output / window_sum
+  }
+
+  /// Returns how many milliseconds the delay time changes per millisecond of grain playback for a grain detuned
+  /// by `cents`. A grain that plays back at `ratio` in reverse moves away from the write head at `1 + ratio`.
+  fn get_drift(cents: f32, is_reversed: bool) -> f32 {
+    let ratio = (cents / 1200.).exp2();
+    if is_reversed {
+      1. + ratio
+    } else {
+      1. - ratio
+    }
+  }
+
+  /// Grains that play faster than the write head start far enough back to never overtake it.
+  fn get_offset(drift: f32, phase: f32, grain_size: f32) -> f32 {
+    if drift < 0. {
+      -drift * (1. - phase) * grain_size
+    } else {
+      drift * phase * grain_size
+    }
   }
 
   fn wrap(x: f32) -> f32 {
@@ -83,3 +188,82 @@ impl Grains {
     }
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::{GrainWindow, Grains};
+  use crate::shared::{
+    constants::MAX_GRAIN_PITCH_JITTER, delay_line::DelayLine, phasor::Phasor, quality::Quality,
+  };
+
+  fn get_output(grains: &mut Grains, pitch_jitter: f32, reverse: f32) -> Vec<f32> {
+    let mut delay_line = DelayLine::new(4096, 1000.);
+    (0..2000)
+      .map(|i| {
+        delay_line.write((i as f32 * 0.37).sin());
+        grains.process(
+          &delay_line,
+          100.,
+          1.,
+          (100., 4, GrainWindow::Hann, 0., pitch_jitter, reverse),
+          3.,
+          Quality::Standard,
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn should_shape_grain_windows() {
+    for window in [
+      GrainWindow::Hann,
+      GrainWindow::Tukey,
+      GrainWindow::Trapezoid,
+    ] {
+      assert!(window.process(0.).abs() < 0.01);
+      assert!((window.process(0.5) - 1.).abs() < 0.01);
+    }
+    assert!(GrainWindow::Hann.process(0.2) < GrainWindow::Tukey.process(0.2));
+    assert_eq!(GrainWindow::Tukey.process(0.4), 1.);
+    assert_eq!(GrainWindow::Trapezoid.process(0.125), 0.5);
+  }
+
+  #[test]
+  fn should_never_read_ahead_of_the_write_head() {
+    let grain_size: f32 = 100.;
+    let ratio = (MAX_GRAIN_PITCH_JITTER / 1200.).exp2();
+    let drift = Grains::get_drift(MAX_GRAIN_PITCH_JITTER, false);
+    let mut phasor = Phasor::new(1000.);
+    let mut previous_read_position: Option<f32> = None;
+
+    // Steps through the lifetime of a grain with the maximum upward jitter at one millisecond per sample and
+    // stops before the phase wraps around into the next grain
+    for time in 1..grain_size as usize {
+      let phase = phasor.process(1000. / grain_size);
+      let offset = Grains::get_offset(drift, phase, grain_size);
+      assert!(offset >= 0.);
+
+      let read_position = time as f32 - offset;
+      if let Some(previous_read_position) = previous_read_position {
+        assert!((read_position - previous_read_position - ratio).abs() < 0.001);
+      }
+      previous_read_position = Some(read_position);
+    }
+  }
+
+  #[test]
+  fn should_reproduce_pitched_and_reversed_grains_with_the_same_seed() {
+    let output = get_output(&mut Grains::new(1000., 1), 50., 0.5);
+    assert_eq!(output, get_output(&mut Grains::new(1000., 1), 50., 0.5));
+    assert_ne!(output, get_output(&mut Grains::new(1000., 2), 50., 0.5));
+    assert_ne!(output, get_output(&mut Grains::new(1000., 1), 0., 0.));
+  }
+
+  #[test]
+  fn should_restart_the_grains_after_a_reset() {
+    let mut grains = Grains::new(1000., 1);
+    let output = get_output(&mut grains, 50., 0.5);
+    grains.reset(1);
+    assert_eq!(get_output(&mut grains, 50., 0.5), output);
+  }
+}
@@ -0,0 +1,81 @@
+use std::simd::f32x4;
+
+pub struct Injection {
+  distribution: f32,
+  rotation: f32,
+  left_gains: f32x4,
+  right_gains: f32x4,
+}
+
+impl Injection {
+  pub fn new() -> Self {
+    Self {
+      distribution: 0.,
+      rotation: 0.,
+      left_gains: f32x4::from_array([1., 0., 0., 0.]),
+      right_gains: f32x4::from_array([0., 1., 0., 0.]),
+    }
+  }
+
+  /// Spreads the stereo input over the delay lines. A distribution of 0 only feeds lines 0 and 1, while 1 feeds
+  /// all lines equally. Rotation shifts the injection vector across the lines and spread sets the stereo width
+  /// of the input.
+  pub fn process(
+    &mut self,
+    (left, right): (f32, f32),
+    (distribution, rotation, spread): (f32, f32, f32),
+  ) -> f32x4 {
+    if distribution != self.distribution || rotation != self.rotation {
+      self.distribution = distribution;
+      self.rotation = rotation;
+      // Keeps the injected power constant
+      let gain = (1. + distribution * distribution).sqrt().recip();
+      self.left_gains = Self::rotate([gain, 0., distribution * gain, 0.], rotation);
+      self.right_gains = Self::rotate([0., gain, 0., distribution * gain], rotation);
+    }
+
+    let mid = (left + right) * 0.5;
+    let side = (left - right) * 0.5 * spread;
+    f32x4::splat(mid + side) * self.left_gains + f32x4::splat(mid - side) * self.right_gains
+  }
+
+  fn rotate(gains: [f32; 4], rotation: f32) -> f32x4 {
+    let position = rotation.rem_euclid(1.) * 4.;
+    let offset = position as usize;
+    let mix = position - offset as f32;
+
+    f32x4::from_array([0, 1, 2, 3].map(|i| {
+      let current = gains[(i + 4 - offset) % 4];
+      let previous = gains[(i + 3 - offset) % 4];
+      current + (previous - current) * mix
+    }))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Injection;
+
+  #[test]
+  fn should_only_feed_first_lines_by_default() {
+    let mut injection = Injection::new();
+    assert_eq!(
+      injection.process((0.5, -0.25), (0., 0., 1.)).to_array(),
+      [0.5, -0.25, 0., 0.]
+    );
+  }
+
+  #[test]
+  fn should_distribute_and_rotate_input() {
+    let mut injection = Injection::new();
+    let distributed = injection.process((1., 1.), (1., 0., 1.)).to_array();
+    let power = distributed.iter().map(|x| x * x).sum::<f32>();
+    assert!((power - 2.).abs() < 0.0001);
+
+    let rotated = injection.process((1., 0.), (0., 0.25, 1.)).to_array();
+    assert_eq!(rotated, [0., 1., 0., 0.]);
+
+    let mono = injection.process((1., 0.), (0., 0., 0.)).to_array();
+    assert_eq!(mono, [0.5, 0.5, 0., 0.]);
+  }
+}
@@ -0,0 +1,138 @@
+use crate::shared::float_ext::FloatExt;
+use std::f32::consts::{FRAC_PI_2, TAU};
+
+const CHAOS_STEPS: f32 = 4.;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum LfoShape {
+  Sine,
+  Triangle,
+  SmoothRandom,
+  Chaos,
+}
+
+impl From<i32> for LfoShape {
+  fn from(value: i32) -> Self {
+    match value {
+      1 => LfoShape::Triangle,
+      2 => LfoShape::SmoothRandom,
+      3 => LfoShape::Chaos,
+      _ => LfoShape::Sine,
+    }
+  }
+}
+
+/// Turns the shared lfo phase into a bipolar modulation signal. The random shapes keep their own state per delay
+/// line, so each line wanders independently and the tail loses its periodic chorus signature.
+pub struct Lfo {
+  rng: fastrand::Rng,
+  previous_phase: f32,
+  step: f32,
+  from: f32,
+  to: f32,
+  chaos: f32,
+}
+
+impl Lfo {
+  pub fn new(seed: u64) -> Self {
+    let mut rng = fastrand::Rng::with_seed(seed);
+    let chaos = rng.f32() * 0.8 + 0.1;
+    Self {
+      rng,
+      previous_phase: 0.,
+      step: 0.,
+      from: 0.,
+      to: 0.,
+      chaos,
+    }
+  }
+
+  /// Restarts the random shapes from `seed`, including the chaos amount and the current segment.
+  pub fn reset(&mut self, seed: u64) {
+    *self = Self::new(seed);
+  }
+
+  pub fn process(&mut self, phase: f32, shape: LfoShape) -> f32 {
+    let output = match shape {
+      LfoShape::Sine => (phase * TAU).fast_sin(),
+      LfoShape::Triangle => 1. - (Self::wrap(phase + 0.25) - 0.5).abs() * 4.,
+      LfoShape::SmoothRandom => self.get_smooth_random(phase),
+      LfoShape::Chaos => self.get_chaos(phase),
+    };
+    self.previous_phase = phase;
+    output
+  }
+
+  /// Picks a new random target every cycle and glides towards it with a raised cosine slew.
+  fn get_smooth_random(&mut self, phase: f32) -> f32 {
+    if phase < self.previous_phase {
+      self.from = self.to;
+      self.to = self.rng.f32() * 2. - 1.;
+    }
+    let slew = (phase * FRAC_PI_2).fast_sin();
+    self.from.mix(self.to, slew * slew)
+  }
+
+  /// Iterates a logistic map a few times per cycle and interpolates linearly between the iterations.
+  fn get_chaos(&mut self, phase: f32) -> f32 {
+    let position = phase * CHAOS_STEPS;
+    let step = position.floor();
+    if step != self.step || phase < self.previous_phase {
+      self.step = step;
+      self.chaos = 3.99 * self.chaos * (1. - self.chaos);
+      // Escape the fixed point at zero that rounding can push the map into
+      if !(0.0001..0.9999).contains(&self.chaos) {
+        self.chaos = self.rng.f32() * 0.8 + 0.1;
+      }
+      self.from = self.to;
+      self.to = self.chaos * 2. - 1.;
+    }
+    self.from.mix(self.to, position - step)
+  }
+
+  fn wrap(x: f32) -> f32 {
+    if x >= 1. {
+      x - 1.
+    } else {
+      x
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::{Lfo, LfoShape};
+
+  fn get_cycles(shape: LfoShape, seed: u64) -> Vec<f32> {
+    let mut lfo = Lfo::new(seed);
+    (0..4000)
+      .map(|i| lfo.process((i % 1000) as f32 / 1000., shape))
+      .collect()
+  }
+
+  #[test]
+  fn should_stay_bipolar_and_continuous() {
+    for shape in [
+      LfoShape::Sine,
+      LfoShape::Triangle,
+      LfoShape::SmoothRandom,
+      LfoShape::Chaos,
+    ] {
+      let output = get_cycles(shape, 1);
+      assert!(output.iter().all(|x| x.abs() <= 1.001));
+      assert!(output.windows(2).all(|x| (x[1] - x[0]).abs() < 0.05));
+    }
+  }
+
+  #[test]
+  fn should_not_repeat_random_shapes_every_cycle() {
+    for shape in [LfoShape::SmoothRandom, LfoShape::Chaos] {
+      let output = get_cycles(shape, 1);
+      assert!(output[1500..2000]
+        .iter()
+        .zip(&output[2500..3000])
+        .any(|(a, b)| (a - b).abs() > 0.01));
+      assert_eq!(output, get_cycles(shape, 1));
+    }
+  }
+}
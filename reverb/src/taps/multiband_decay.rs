@@ -0,0 +1,136 @@
+use std::{
+  f32::consts::TAU,
+  simd::{f32x4, num::SimdFloat, StdFloat},
+};
+
+// The multipliers scale the decay time up to this decay. Above it every band keeps the ratio it has here, so the
+// multipliers still shorten a band when the decay reaches or exceeds unity.
+const MAX_BAND_DECAY: f32 = 0.95;
+
+pub struct MultibandDecay {
+  t: f32,
+  time_fractions: f32x4,
+  low_z: f32x4,
+  high_z: f32x4,
+  crossovers: (f32, f32),
+  b1: (f32x4, f32x4),
+  decay: f32,
+  multipliers: (f32, f32, f32),
+  gains: (f32x4, f32x4, f32x4),
+}
+
+impl MultibandDecay {
+  pub fn new(sample_rate: f32, time_fractions: [f32; 4]) -> Self {
+    Self {
+      t: sample_rate.recip() * -TAU,
+      time_fractions: f32x4::from_array(time_fractions),
+      low_z: f32x4::splat(0.),
+      high_z: f32x4::splat(0.),
+      crossovers: (0., 0.),
+      b1: (f32x4::splat(0.), f32x4::splat(0.)),
+      decay: 0.,
+      multipliers: (0., 0., 0.),
+      gains: (f32x4::splat(0.), f32x4::splat(0.), f32x4::splat(0.)),
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.low_z = f32x4::splat(0.);
+    self.high_z = f32x4::splat(0.);
+  }
+
+  /// Splits the input of each delay line into a low, mid and high band and applies a feedback gain per band.
+  /// The decay sets the feedback gain of the longest delay line. The multipliers scale the decay time per band.
+  pub fn process(
+    &mut self,
+    input: f32x4,
+    decay: f32,
+    multipliers: (f32, f32, f32),
+    crossovers: (f32, f32),
+  ) -> f32x4 {
+    if decay != self.decay || multipliers != self.multipliers {
+      self.decay = decay;
+      self.multipliers = multipliers;
+      self.gains = (
+        self.get_gain(decay, multipliers.0),
+        self.get_gain(decay, multipliers.1),
+        self.get_gain(decay, multipliers.2),
+      );
+    }
+    if crossovers != self.crossovers {
+      self.crossovers = crossovers;
+      self.b1 = (
+        f32x4::splat((crossovers.0 * self.t).exp()),
+        f32x4::splat((crossovers.1 * self.t).exp()),
+      );
+    }
+
+    let one = f32x4::splat(1.);
+    self.low_z = input * (one - self.b1.0) + self.low_z * self.b1.0;
+    self.high_z = input * (one - self.b1.1) + self.high_z * self.b1.1;
+    let low = self.low_z;
+    let mid = self.high_z - self.low_z;
+    let high = input - self.high_z;
+
+    low * self.gains.0 + mid * self.gains.1 + high * self.gains.2
+  }
+
+  /// Scales the gain to the length of each delay line, so all lines share the same decay time. No band sustains
+  /// longer than the decay itself, so a band never grows faster than the decay lets the tank grow.
+  fn get_gain(&self, decay: f32, multiplier: f32) -> f32x4 {
+    if decay <= 0. {
+      f32x4::splat(0.)
+    } else {
+      let band_decay = decay.min(MAX_BAND_DECAY);
+      let excess = decay.max(MAX_BAND_DECAY) / MAX_BAND_DECAY;
+      let gain =
+        (f32x4::splat(band_decay.ln() / multiplier + excess.ln()) * self.time_fractions).exp();
+      let max_gain = (f32x4::splat(decay.max(1.).ln()) * self.time_fractions).exp();
+      gain.simd_min(max_gain)
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::MultibandDecay;
+  use std::simd::f32x4;
+
+  #[test]
+  fn should_scale_gain_to_delay_line_length() {
+    let mut multiband_decay = MultibandDecay::new(44100., [0.25, 0.5, 0.75, 1.]);
+    let output = multiband_decay
+      .process(f32x4::splat(1.), 0.5, (1., 1., 1.), (250., 4000.))
+      .to_array();
+
+    let expected = [0.5f32.powf(0.25), 0.5f32.powf(0.5), 0.5f32.powf(0.75), 0.5];
+    output
+      .into_iter()
+      .zip(expected)
+      .for_each(|(left, right)| assert!((left - right).abs() < 1e-6));
+  }
+
+  #[test]
+  fn should_keep_the_bands_ordered_and_bounded_from_unity_decay() {
+    for decay in [1., 1.2] {
+      let multiband_decay = MultibandDecay::new(44100., [0.25, 0.5, 0.75, 1.]);
+      let gains: Vec<[f32; 4]> = [0.25, 0.5, 1., 2., 4.]
+        .into_iter()
+        .map(|multiplier| multiband_decay.get_gain(decay, multiplier).to_array())
+        .collect();
+
+      for pair in gains.windows(2) {
+        assert!(pair[0]
+          .iter()
+          .zip(pair[1])
+          .all(|(left, right)| *left <= right));
+      }
+      // Shorter bands still sustain less than the decay, even when the decay is at or above unity
+      assert!(gains[1]
+        .iter()
+        .zip(gains[2])
+        .all(|(left, right)| *left < right));
+      assert!(gains[4][3] <= decay);
+    }
+  }
+}
@@ -13,6 +13,10 @@ impl OnePoleFilter {
     }
   }
 
+  pub fn reset(&mut self) {
+    self.z = f32x4::splat(0.);
+  }
+
   pub fn process(&mut self, input: f32x4, absorb: f32) -> f32x4 {
     let b1 = f32x4::splat(absorb * self.t);
     let a0 = f32x4::splat(1.0) - b1;
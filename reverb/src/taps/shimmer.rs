@@ -1,31 +1,57 @@
-use crate::shared::{
-  float_ext::FloatExt,
-  phasor::Phasor,
-  stereo_delay_line::{Interpolation, StereoDelayLine},
-};
-use std::f32::consts::PI;
-
-const FREQUENCY: f32 = -5.;
-const WINDOW_SIZE: f32 = 200.;
+mod voice;
+use crate::shared::{quality::Quality, stereo_delay_line::StereoDelayLine};
+pub use voice::ShimmerMode;
+use voice::{Voice, MAX_DELAY_TIME};
 
 pub struct Shimmer {
   delay_line: StereoDelayLine,
-  phasor: Phasor,
+  voices: [Voice; 2],
 }
 
 impl Shimmer {
   pub fn new(sample_rate: f32) -> Self {
-    let delay_length = (sample_rate * WINDOW_SIZE / 1000.) as usize;
+    let delay_length = (sample_rate * MAX_DELAY_TIME / 1000.) as usize;
 
     Self {
       delay_line: StereoDelayLine::new(delay_length, sample_rate),
-      phasor: Phasor::new(sample_rate),
+      voices: [Voice::new(sample_rate), Voice::new(sample_rate)],
     }
   }
 
-  pub fn process(&mut self, dry: (f32, f32), wet: (f32, f32), mix: f32) -> (f32, f32) {
+  pub fn reset(&mut self) {
+    self.delay_line.reset();
+    self.voices.iter_mut().for_each(Voice::reset);
+  }
+
+  /// The second voice is mixed in by `second_level`. Spread pans the first voice to the left and
+  /// the second voice to the right.
+  pub fn process(
+    &mut self,
+    dry: (f32, f32),
+    wet: (f32, f32),
+    mix: f32,
+    (pitch, second_pitch, second_level, spread): (f32, f32, f32, f32),
+    mode: ShimmerMode,
+    quality: Quality,
+  ) -> (f32, f32) {
     let out = if mix > 0. {
-      let grains_out = self.apply_shimmer();
+      let interp = quality.modulated_interpolation();
+      let first_voice = self.voices[0].process(&self.delay_line, pitch, mode, interp);
+      let grains_out = if second_level > 0. {
+        let second_voice = self.voices[1].process(&self.delay_line, second_pitch, mode, interp);
+        // Scales the sum back down, so two voices don't feed more energy into the tank than one
+        let first_gain = (1. + second_level).recip();
+        let second_gain = second_level * first_gain;
+        // The first voice is only panned as far as the second voice is audible
+        let first_pan = 1. - spread * second_level;
+        let second_pan = 1. - spread;
+        (
+          first_voice.0 * first_gain + second_voice.0 * second_gain * second_pan,
+          first_voice.1 * first_gain * first_pan + second_voice.1 * second_gain,
+        )
+      } else {
+        first_voice
+      };
       Self::mix(dry, grains_out, mix)
     } else {
       dry
@@ -42,34 +68,77 @@ impl Shimmer {
       left.1 + (right.1 - left.1) * factor,
     )
   }
+}
 
-  fn apply_shimmer(&mut self) -> (f32, f32) {
-    let main_phase = self.phasor.process(FREQUENCY);
+#[cfg(test)]
+mod tests {
+  use super::{Shimmer, ShimmerMode};
+  use crate::shared::quality::Quality;
+  use std::f32::consts::TAU;
 
-    (0..2)
-      .map(|index| {
-        let phase = if index == 0 {
-          main_phase
-        } else {
-          Self::wrap(main_phase + 0.5)
-        };
-        let time = phase * WINDOW_SIZE;
-        let window = (phase * PI).fast_sin();
-        let window = window * window;
+  fn get_output(second_level: f32, mode: ShimmerMode) -> (f32, f32) {
+    let mut shimmer = Shimmer::new(44100.);
+    (0..44100).fold((0., 0.), |_, _| {
+      shimmer.process(
+        (1., 1.),
+        (1., 1.),
+        1.,
+        (0., 0., second_level, 0.),
+        mode,
+        Quality::Standard,
+      )
+    })
+  }
 
-        let delay_line_out = self.delay_line.read(time, Interpolation::Linear);
-        (delay_line_out.0 * window, delay_line_out.1 * window)
-      })
-      .fold((0., 0.), |result, item| {
-        (result.0 + item.0, result.1 + item.1)
+  #[test]
+  fn should_not_add_gain_with_second_voice() {
+    let single_voice = get_output(0., ShimmerMode::Classic);
+    let dual_voice = get_output(1., ShimmerMode::Classic);
+    assert!((single_voice.0 - 1.).abs() < 0.01);
+    assert!((dual_voice.0 - single_voice.0).abs() < 0.001);
+    assert!((dual_voice.1 - single_voice.1).abs() < 0.001);
+  }
+
+  #[test]
+  fn should_keep_unity_gain_in_high_quality_mode() {
+    let output = get_output(0., ShimmerMode::HighQuality);
+    assert!((output.0 - 1.).abs() < 0.01);
+    assert!((output.1 - 1.).abs() < 0.01);
+  }
+
+  fn get_amplitude_modulation(mode: ShimmerMode) -> f32 {
+    let sample_rate = 44100.;
+    let mut shimmer = Shimmer::new(sample_rate);
+    let output: Vec<f32> = (0..88200)
+      .map(|i| {
+        let x = (i as f32 * 220. * TAU / sample_rate).sin();
+        shimmer
+          .process(
+            (x, x),
+            (0., 0.),
+            1.,
+            (3., 0., 0., 0.),
+            mode,
+            Quality::Standard,
+          )
+          .0
       })
+      .collect();
+    // Measures the spread of the rms over blocks of 10 ms
+    let rms: Vec<f32> = output[44100..]
+      .chunks(441)
+      .map(|chunk| (chunk.iter().map(|x| x * x).sum::<f32>() / chunk.len() as f32).sqrt())
+      .collect();
+    let (min, max) = rms.iter().fold((f32::MAX, 0_f32), |(min, max), x| {
+      (min.min(*x), max.max(*x))
+    });
+    max - min
   }
 
-  fn wrap(x: f32) -> f32 {
-    if x >= 1. {
-      x - 1.
-    } else {
-      x
-    }
+  #[test]
+  fn should_reduce_amplitude_modulation_in_high_quality_mode() {
+    let classic = get_amplitude_modulation(ShimmerMode::Classic);
+    let high_quality = get_amplitude_modulation(ShimmerMode::HighQuality);
+    assert!(high_quality < classic * 0.25);
   }
 }
@@ -0,0 +1,214 @@
+use crate::shared::{
+  float_ext::FloatExt,
+  phasor::Phasor,
+  stereo_delay_line::{Interpolation, StereoDelayLine},
+};
+use std::f32::consts::PI;
+
+const GRAIN_FREQUENCY: f32 = 5.;
+const MIN_WINDOW_SIZE: f32 = 50.;
+const MAX_WINDOW_SIZE: f32 = 200.;
+const MAX_GRAINS: usize = 4;
+// The high quality mode searches this many candidate offsets within the splice range in ms
+const SPLICE_CANDIDATES: usize = 16;
+const MAX_SPLICE_OFFSET: f32 = 10.;
+const CORRELATION_LENGTH: usize = 32;
+pub const MAX_DELAY_TIME: f32 = MAX_WINDOW_SIZE + MAX_SPLICE_OFFSET * 2.;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ShimmerMode {
+  Classic,
+  HighQuality,
+}
+
+impl From<i32> for ShimmerMode {
+  fn from(value: i32) -> Self {
+    match value {
+      1 => ShimmerMode::HighQuality,
+      _ => ShimmerMode::Classic,
+    }
+  }
+}
+
+pub struct Voice {
+  phasor: Phasor,
+  sample_period: f32,
+  pitch: f32,
+  frequency: f32,
+  window_size: f32,
+  phases: [f32; MAX_GRAINS],
+  splice_offsets: [f32; MAX_GRAINS],
+}
+
+impl Voice {
+  pub fn new(sample_rate: f32) -> Self {
+    Self {
+      phasor: Phasor::new(sample_rate),
+      sample_period: 1000. / sample_rate,
+      pitch: f32::NAN,
+      frequency: 0.,
+      window_size: MAX_WINDOW_SIZE,
+      phases: [0.; MAX_GRAINS],
+      splice_offsets: [0.; MAX_GRAINS],
+    }
+  }
+
+  pub fn reset(&mut self) {
+    self.phasor.reset();
+    self.phases = [0.; MAX_GRAINS];
+    self.splice_offsets = [0.; MAX_GRAINS];
+  }
+
+  pub fn process(
+    &mut self,
+    delay_line: &StereoDelayLine,
+    pitch: f32,
+    mode: ShimmerMode,
+    interp: Interpolation,
+  ) -> (f32, f32) {
+    if pitch != self.pitch {
+      self.set_pitch(pitch);
+    }
+    let main_phase = self.phasor.process(self.frequency);
+
+    match mode {
+      ShimmerMode::Classic => self.process_grains::<2>(delay_line, main_phase, interp, false),
+      ShimmerMode::HighQuality => {
+        let output = self.process_grains::<MAX_GRAINS>(delay_line, main_phase, interp, true);
+        // Four overlapping sin² windows add up to two
+        (output.0 * 0.5, output.1 * 0.5)
+      }
+    }
+  }
+
+  fn process_grains<const N: usize>(
+    &mut self,
+    delay_line: &StereoDelayLine,
+    main_phase: f32,
+    interp: Interpolation,
+    should_splice: bool,
+  ) -> (f32, f32) {
+    (0..N).fold((0., 0.), |result, index| {
+      let phase = Self::wrap(main_phase + index as f32 / N as f32);
+      // A grain restarts when its phase wraps around, which is where its window is silent
+      if (phase - self.phases[index]).abs() > 0.5 {
+        self.splice_offsets[index] = if should_splice {
+          self.find_splice_offset(delay_line, index, phase, N)
+        } else {
+          0.
+        };
+      }
+      self.phases[index] = phase;
+
+      let time = phase * self.window_size + self.splice_offsets[index];
+      let window = (phase * PI).fast_sin();
+      let window = window * window;
+
+      let delay_line_out = delay_line.read(time, interp);
+      (
+        result.0 + delay_line_out.0 * window,
+        result.1 + delay_line_out.1 * window,
+      )
+    })
+  }
+
+  /// Finds the offset at which the restarting grain best matches the other grains, so the
+  /// overlapping grains don't cancel each other out.
+  fn find_splice_offset(
+    &self,
+    delay_line: &StereoDelayLine,
+    restarting_grain: usize,
+    phase: f32,
+    grain_count: usize,
+  ) -> f32 {
+    let mut reference = [0.; CORRELATION_LENGTH];
+    for grain in (0..grain_count).filter(|grain| *grain != restarting_grain) {
+      let time = self.phases[grain] * self.window_size + self.splice_offsets[grain];
+      let window = (self.phases[grain] * PI).fast_sin();
+      let window = window * window;
+      for (i, x) in reference.iter_mut().enumerate() {
+        *x += self.read_mono(delay_line, time, i) * window;
+      }
+    }
+    let time = phase * self.window_size;
+    let step_size = MAX_SPLICE_OFFSET / SPLICE_CANDIDATES as f32;
+
+    (0..SPLICE_CANDIDATES)
+      .map(|candidate| {
+        let offset = candidate as f32 * step_size;
+        let (correlation, energy) =
+          reference
+            .iter()
+            .enumerate()
+            .fold((0., 0.), |(correlation, energy), (i, reference)| {
+              let x = self.read_mono(delay_line, time + offset, i);
+              (correlation + reference * x, energy + x * x)
+            });
+        (offset, correlation / (energy + f32::EPSILON).sqrt())
+      })
+      .fold((0., f32::MIN), |best, candidate| {
+        if candidate.1 > best.1 {
+          candidate
+        } else {
+          best
+        }
+      })
+      .0
+  }
+
+  fn read_mono(&self, delay_line: &StereoDelayLine, time: f32, index: usize) -> f32 {
+    let (left, right) = delay_line.read(
+      time + index as f32 * self.sample_period,
+      Interpolation::Step,
+    );
+    left + right
+  }
+
+  /// Sets the interval in semitones. The window length follows the interval, so the grains repeat
+  /// at roughly the same rate and shift the pitch by the right ratio.
+  fn set_pitch(&mut self, pitch: f32) {
+    let ratio = (pitch / 12.).exp2();
+    let delay_time_change = 1. - ratio;
+    self.pitch = pitch;
+    self.window_size =
+      (delay_time_change.abs() * 1000. / GRAIN_FREQUENCY).clamp(MIN_WINDOW_SIZE, MAX_WINDOW_SIZE);
+    self.frequency = delay_time_change * 1000. / self.window_size;
+  }
+
+  fn wrap(x: f32) -> f32 {
+    if x >= 1. {
+      x - 1.
+    } else {
+      x
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::Voice;
+
+  #[test]
+  fn should_adapt_window_to_interval() {
+    let mut voice = Voice::new(44100.);
+
+    voice.set_pitch(12.);
+    assert_eq!(voice.window_size, 200.);
+    assert!((voice.frequency + 5.).abs() < 0.001);
+
+    voice.set_pitch(7.);
+    assert!((voice.window_size - 99.7).abs() < 0.1);
+    assert!((voice.frequency + 5.).abs() < 0.001);
+
+    voice.set_pitch(-12.);
+    assert_eq!(voice.window_size, 100.);
+    assert!((voice.frequency - 5.).abs() < 0.001);
+
+    voice.set_pitch(24.);
+    assert_eq!(voice.window_size, 200.);
+    assert!((voice.frequency + 15.).abs() < 0.001);
+
+    voice.set_pitch(1.);
+    assert_eq!(voice.window_size, 50.);
+  }
+}
@@ -1,7 +1,4 @@
-mod biquad_filter;
-use biquad_filter::BiquadFilter;
-mod bilinear_transform;
-use bilinear_transform::BilinearTransform;
+use crate::shared::{bilinear_transform::BilinearTransform, biquad_filter::BiquadFilter};
 
 const C1: f32 = 5.6e-9;
 const C2: f32 = 5.6e-9;
@@ -10,10 +7,16 @@ const R2: f32 = 2250.;
 const RF1: f32 = 47000.;
 const RF2: f32 = 47000.;
 const R_TILT: f32 = 140000.;
+// While tilt is moving, coefficients are interpolated from a table with this many steps
+const TABLE_SIZE: usize = 256;
 
 pub struct TiltFilter {
   bilinear_transform: BilinearTransform,
   biquad_filter: BiquadFilter,
+  table: Vec<([f32; 3], [f32; 3])>,
+  coefficients: ([f32; 3], [f32; 3]),
+  tilt: f32,
+  is_exact: bool,
 }
 
 impl TiltFilter {
@@ -29,19 +32,59 @@ impl TiltFilter {
   const C2RF1: f32 = C2 * RF1;
 
   pub fn new(sample_rate: f32) -> Self {
-    Self {
+    let mut tilt_filter = Self {
       bilinear_transform: BilinearTransform::new(sample_rate),
       biquad_filter: BiquadFilter::new(),
-    }
+      table: Vec::with_capacity(TABLE_SIZE + 1),
+      coefficients: ([1., 0., 0.], [1., 0., 0.]),
+      tilt: f32::NAN,
+      is_exact: false,
+    };
+    tilt_filter.table = (0..=TABLE_SIZE)
+      .map(|i| tilt_filter.get_z_domain_coefficients(i as f32 / TABLE_SIZE as f32))
+      .collect();
+    tilt_filter
+  }
+
+  pub fn reset(&mut self) {
+    self.biquad_filter.reset();
   }
 
   pub fn process(&mut self, input: (f32, f32), tilt: f32) -> (f32, f32) {
-    let s_domain_coefficients = self.get_s_domain_coefficients(tilt);
-    let z_domain_coefficients = self.bilinear_transform.process(s_domain_coefficients);
-    self.biquad_filter.process(input, z_domain_coefficients)
+    if tilt != self.tilt {
+      self.tilt = tilt;
+      self.is_exact = false;
+      self.coefficients = self.get_interpolated_coefficients(tilt);
+      self.biquad_filter.process(input, self.coefficients)
+    } else {
+      if !self.is_exact {
+        // Tilt has settled, so the exact coefficients only need to be computed once
+        self.is_exact = true;
+        self.coefficients = self.get_z_domain_coefficients(tilt);
+      }
+      self.biquad_filter.process(input, self.coefficients)
+    }
+  }
+
+  fn get_interpolated_coefficients(&self, tilt: f32) -> ([f32; 3], [f32; 3]) {
+    let position = tilt.clamp(0., 1.) * TABLE_SIZE as f32;
+    let index = (position as usize).min(TABLE_SIZE - 1);
+    let mix = position - index as f32;
+    let (b_left, a_left) = self.table[index];
+    let (b_right, a_right) = self.table[index + 1];
+
+    (
+      [0, 1, 2].map(|i| b_left[i] + (b_right[i] - b_left[i]) * mix),
+      [0, 1, 2].map(|i| a_left[i] + (a_right[i] - a_left[i]) * mix),
+    )
+  }
+
+  fn get_z_domain_coefficients(&self, tilt: f32) -> ([f32; 3], [f32; 3]) {
+    let s_domain_coefficients = Self::get_s_domain_coefficients(tilt);
+    self.bilinear_transform.process(s_domain_coefficients)
   }
 
-  fn get_s_domain_coefficients(&self, tilt: f32) -> ([f32; 3], [f32; 3]) {
+  pub fn get_s_domain_coefficients(tilt: f32) -> ([f32; 3], [f32; 3]) {
     let r_tilt_a = R_TILT * tilt;
     let r_tilt_b = R_TILT * (1. - tilt);
 
@@ -77,3 +120,46 @@ impl TiltFilter {
     ([b0, b1, b2], [a0, a1, a2])
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::TiltFilter;
+  use crate::{params::Params, shared::biquad_filter::BiquadFilter};
+  use std::f32::consts::TAU;
+
+  #[test]
+  fn should_match_per_sample_coefficients_while_tilt_moves() {
+    let sample_rate = 44100.;
+    let mut tilt_filter = TiltFilter::new(sample_rate);
+    let reference = TiltFilter::new(sample_rate);
+    let mut reference_biquad_filter = BiquadFilter::new();
+
+    let max_error = (0..sample_rate as usize).fold(0., |max_error: f32, i| {
+      let x = (i as f32 * 440. * TAU / sample_rate).sin();
+      // Sweeps the tilt param from -1 to 1 and back within a second, which covers the whole table
+      let tilt = Params::map_tilt(-(i as f32 / sample_rate * TAU).cos());
+      let output = tilt_filter.process((x, x), tilt);
+      let expected =
+        reference_biquad_filter.process((x, x), reference.get_z_domain_coefficients(tilt));
+      max_error.max((output.0 - expected.0).abs())
+    });
+    assert!(max_error < 0.001);
+  }
+
+  #[test]
+  fn should_use_exact_coefficients_while_tilt_is_static() {
+    let sample_rate = 44100.;
+    let mut tilt_filter = TiltFilter::new(sample_rate);
+    let mut reference_biquad_filter = BiquadFilter::new();
+    let coefficients = tilt_filter.get_z_domain_coefficients(0.3);
+
+    tilt_filter.process((0., 0.), 0.3);
+    for i in 0..1000 {
+      let x = (i as f32 * 440. * TAU / sample_rate).sin();
+      assert_eq!(
+        tilt_filter.process((x, x), 0.3),
+        reference_biquad_filter.process((x, x), coefficients)
+      );
+    }
+  }
+}